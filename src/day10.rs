@@ -1,8 +1,11 @@
-use std::collections::{HashMap, VecDeque, HashSet};
+use std::{collections::{HashMap, VecDeque, HashSet}, fmt::Display};
 
 use aoc_runner_derive::aoc;
+use itertools::Itertools;
 use nom::{IResult, combinator::{map_res, all_consuming, opt}, character::complete::{line_ending, one_of}, multi::many1, sequence::terminated};
 
+use crate::diagnostics::DiagnosticsReport;
+
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 enum Pipe {
@@ -63,23 +66,25 @@ impl TryFrom<HashSet<Direction>> for Pipe {
     }
 }
 
-fn parse_pipe(input: &str) -> IResult<&str, Pipe> {
-    map_res(
-        one_of("|-LJ7F.S"),
-        |chr| {
-            match chr {
-                '|' => Ok(Pipe::Vertical),
-                '-' => Ok(Pipe::Horizontal),
-                'L' => Ok(Pipe::NorthEast),
-                'J' => Ok(Pipe::NorthWest),
-                '7' => Ok(Pipe::SouthWest),
-                'F' => Ok(Pipe::SouthEast),
-                '.' => Ok(Pipe::Ground),
-                'S' => Ok(Pipe::Start),
-                _ => Err("Invalid pipe")
-            }
+impl TryFrom<char> for Pipe {
+    type Error = &'static str;
+    fn try_from(value: char) -> Result<Self, Self::Error> {
+        match value {
+            '|' => Ok(Pipe::Vertical),
+            '-' => Ok(Pipe::Horizontal),
+            'L' => Ok(Pipe::NorthEast),
+            'J' => Ok(Pipe::NorthWest),
+            '7' => Ok(Pipe::SouthWest),
+            'F' => Ok(Pipe::SouthEast),
+            '.' => Ok(Pipe::Ground),
+            'S' => Ok(Pipe::Start),
+            _ => Err("Invalid pipe")
         }
-    )(input)
+    }
+}
+
+fn parse_pipe(input: &str) -> IResult<&str, Pipe> {
+    map_res(one_of("|-LJ7F.S"), Pipe::try_from)(input)
 }
 
 fn parse_line(input: &str) -> IResult<&str, Vec<Pipe>> {
@@ -113,12 +118,12 @@ impl Direction {
         }
     }
 
-    fn get_loc(&self, (col, row): (i32, i32)) -> (i32, i32) {
+    fn get_loc(&self, (row, col): (i32, i32)) -> (i32, i32) {
         match *self {
-            Direction::North => (col - 1, row),
-            Direction::East => (col, row + 1),
-            Direction::South => (col + 1, row),
-            Direction::West => (col, row - 1),
+            Direction::North => (row - 1, col),
+            Direction::East => (row, col + 1),
+            Direction::South => (row + 1, col),
+            Direction::West => (row, col - 1),
         }
     }
 }
@@ -135,62 +140,61 @@ impl From<((i32, i32), Pipe)> for Node {
     }
 }
 
-#[aoc(day10, part1)]
-fn part1(input: &str) -> u32 {
-    let pipe_input = parse(input);
+const ALL_DIRECTIONS: [Direction; 4] = [
+    Direction::North,
+    Direction::East,
+    Direction::South,
+    Direction::West,
+];
 
-    let mut starting_loc = None;
-    let mut loc_node_map = pipe_input.into_iter().enumerate().fold(HashMap::new(), |mut map, (col, pipe_row)| {
-        map.extend(pipe_row.into_iter().enumerate().filter_map(|(row, pipe)| {
-            let (col, row) = (col as i32, row as i32);
-            if pipe == Pipe::Start { starting_loc = Some((col, row)); }
-            if let Ok(node) = Node::try_from(((col, row), pipe)) {
-                Some(((col, row), node))
-            } else {
-                None
-            }
-        }));
-        map
-    });
-    let starting_loc = starting_loc.unwrap();
+fn pipe_at(pipe_input: &[Vec<Pipe>], (line, col): (i32, i32)) -> Option<Pipe> {
+    let line = pipe_input.get(usize::try_from(line).ok()?)?;
+    line.get(usize::try_from(col).ok()?).copied()
+}
 
-    loc_node_map.entry(starting_loc).and_modify(|node| { node.distance = Some(0); });
-    let mut max_distance = 0;
-    let mut locs_to_check = VecDeque::from_iter(vec![
-        Direction::North,
-        Direction::East,
-        Direction::South,
-        Direction::West,
-    ].into_iter().filter_map(|dir| {
-        let neighbor_loc = dir.get_loc(starting_loc);
-        if let Some(node) = loc_node_map.get_mut(&neighbor_loc) {
-            if node.shape.connects_to(dir.opposite()) {
-                node.distance = Some(1);
-                max_distance = 1;
-                Some(neighbor_loc)
-            } else {
-                None
-            }
-        } else {
-            None
-        }
-    }));
+// a tile and which of its pipe ends is meant
+type PipeEnd = ((i32, i32), Direction);
 
-    while let Some(location) = locs_to_check.pop_front() {
-        let node = loc_node_map.get(&location).unwrap().clone();
-        node.shape.vec_neighbors().unwrap().into_iter().for_each(|dir| {
-            if let Some(neighbor_node) = loc_node_map.get_mut(&dir.get_loc(location)) {
-                if neighbor_node.shape != Pipe::Start && neighbor_node.distance.is_none() {
-                    let new_dist = node.distance.unwrap() + 1;
-                    neighbor_node.distance = Some(new_dist);
-                    max_distance = new_dist;
-                    locs_to_check.push_back(neighbor_node.location);
-                }
-            }
-        });
+// follows the pipes leaving `start` in `dir` until they come back around to it, or fails with the
+// tile and direction of the first pipe end that has nothing connecting back to it
+fn walk_from(pipe_input: &[Vec<Pipe>], start: (i32, i32), mut dir: Direction) -> Result<Vec<(i32, i32)>, PipeEnd> {
+    let mut path = vec![start];
+    let mut location = start;
+    loop {
+        let next = dir.get_loc(location);
+        if next == start {
+            return Ok(path);
+        }
+        if !pipe_at(pipe_input, next).is_some_and(|pipe| pipe.connects_to(dir.opposite())) {
+            return Err((location, dir));
+        }
+        path.push(next);
+        location = next;
+        let came_from = dir.opposite();
+        dir = pipe_at(pipe_input, location).and_then(|pipe| pipe.vec_neighbors()).unwrap()
+            .into_iter().find(|&next_dir| next_dir != came_from).unwrap();
     }
+}
+
+fn find_starts(pipe_input: &[Vec<Pipe>]) -> Vec<(i32, i32)> {
+    pipe_input.iter().enumerate().flat_map(|(line, pipe_row)| {
+        pipe_row.iter().positions(|&pipe| pipe == Pipe::Start).map(move |col| (line as i32, col as i32))
+    }).collect()
+}
+
+// the main loop's tiles in the order they are walked, starting from S
+fn trace_loop(pipe_input: &[Vec<Pipe>]) -> Vec<(i32, i32)> {
+    let starting_loc = find_starts(pipe_input)[0];
+
+    ALL_DIRECTIONS.into_iter().find_map(|dir| walk_from(pipe_input, starting_loc, dir).ok()).unwrap()
+}
+
+#[aoc(day10, part1)]
+fn part1(input: &str) -> u32 {
+    let pipe_input = parse(input);
 
-    max_distance
+    // the farthest point is halfway around the loop
+    trace_loop(&pipe_input).len() as u32 / 2
 }
 
 
@@ -205,16 +209,16 @@ enum Enclosed {
 #[aoc(day10, part2)]
 fn part2(input: &str) -> u32 {
     let pipe_input = parse(input);
-    let max_col = pipe_input.len() as i32;
-    let max_row = pipe_input[0].len() as i32;
+    let height = pipe_input.len() as i32;
+    let width = pipe_input[0].len() as i32;
 
     let mut starting_loc = None;
-    let mut loc_node_map = pipe_input.into_iter().enumerate().fold(HashMap::new(), |mut map, (col, pipe_row)| {
-        map.extend(pipe_row.into_iter().enumerate().filter_map(|(row, pipe)| {
-            let (col, row) = (col as i32, row as i32);
-            if pipe == Pipe::Start { starting_loc = Some((col, row)); }
-            if let Ok(node) = Node::try_from(((col, row), pipe)) {
-                Some(((col, row), node))
+    let mut loc_node_map = pipe_input.into_iter().enumerate().fold(HashMap::new(), |mut map, (line, pipe_row)| {
+        map.extend(pipe_row.into_iter().enumerate().filter_map(|(col, pipe)| {
+            let (line, col) = (line as i32, col as i32);
+            if pipe == Pipe::Start { starting_loc = Some((line, col)); }
+            if let Ok(node) = Node::try_from(((line, col), pipe)) {
+                Some(((line, col), node))
             } else {
                 None
             }
@@ -279,9 +283,9 @@ fn part2(input: &str) -> u32 {
         });
     }
 
-    (0..max_col).map(|col| {
-        let (spaces, _) = (0..max_row).fold((0, Enclosed::Outside), |(count, enclosed), row| {
-            let node = loc_node_map.get(&(col, row)).unwrap();
+    (0..height).map(|line| {
+        let (spaces, _) = (0..width).fold((0, Enclosed::Outside), |(count, enclosed), col| {
+            let node = loc_node_map.get(&(line, col)).unwrap();
             let part_of_main_loop = node.distance.is_some();
             match (node.shape, enclosed) {
                 (Pipe::NorthEast, Enclosed::Inside) => if part_of_main_loop { (count, Enclosed::FromInside(Direction::North)) }
@@ -309,9 +313,313 @@ fn part2(input: &str) -> u32 {
 }
 
 
+// the shoelace formula gives the loop's area, and Pick's theorem (A = I + B/2 - 1) turns
+// that into the number of tiles strictly inside it given the B tiles on the loop itself
+#[aoc(day10, part2, shoelace)]
+fn part2_shoelace(input: &str) -> u32 {
+    let pipe_input = parse(input);
+    let path = trace_loop(&pipe_input);
+
+    let twice_area = path.iter().circular_tuple_windows().map(|(&(line, col), &(next_line, next_col))| {
+        line as i64 * next_col as i64 - next_line as i64 * col as i64
+    }).sum::<i64>().abs();
+    ((twice_area - path.len() as i64) / 2 + 1) as u32
+}
+
+
+impl From<Pipe> for char {
+    fn from(pipe: Pipe) -> Self {
+        match pipe {
+            Pipe::Vertical => '|',
+            Pipe::Horizontal => '-',
+            Pipe::NorthEast => 'L',
+            Pipe::NorthWest => 'J',
+            Pipe::SouthWest => '7',
+            Pipe::SouthEast => 'F',
+            Pipe::Ground => '.',
+            Pipe::Start => 'S',
+        }
+    }
+}
+
+fn render(pipe_input: &[Vec<Pipe>]) -> String {
+    pipe_input.iter().map(|pipe_row| pipe_row.iter().map(|&pipe| char::from(pipe)).collect::<String>()).join("\n")
+}
+
+impl Display for Direction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Direction::North => write!(f, "north"),
+            Direction::East => write!(f, "east"),
+            Direction::South => write!(f, "south"),
+            Direction::West => write!(f, "west"),
+        }
+    }
+}
+
+// locations are (line, col) from zero like everywhere else in here, but are shown from one
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum PipeDiagnostic {
+    NoStart,
+    MultipleStarts { locations: Vec<(i32, i32)> },
+    AmbiguousStart { location: (i32, i32), connections: Vec<Direction> },
+    MultipleLoops { location: (i32, i32), shapes: Vec<Pipe> },
+    DanglingEnd { location: (i32, i32), direction: Direction },
+    DisconnectedFragment { location: (i32, i32), tiles: usize, closed: bool }
+}
+impl Display for PipeDiagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let show = |(line, col): (i32, i32)| format!("{}:{}", line + 1, col + 1);
+        match self {
+            Self::NoStart => write!(f, "no S tile on the map"),
+            Self::MultipleStarts { locations } => write!(f, "S tiles at {}", locations.iter().copied().map(show).join(", ")),
+            Self::AmbiguousStart { location, connections } => write!(f, "{}: S connects {}, expected exactly two pipes",
+                show(*location), if connections.is_empty() { "nowhere".to_string() } else { connections.iter().join(", ") }),
+            Self::MultipleLoops { location, shapes } => write!(f, "{}: S closes a loop as any of {}",
+                show(*location), shapes.iter().map(|&shape| char::from(shape)).join(", ")),
+            Self::DanglingEnd { location, direction } => write!(f, "{}: pipe end to the {direction} leads nowhere", show(*location)),
+            Self::DisconnectedFragment { location, tiles, closed } => write!(f, "{}: {} fragment of {tiles} pipes apart from S",
+                show(*location), if *closed { "closed" } else { "open" })
+        }
+    }
+}
+
+// pipe tiles that connect to each other, S included, grouped in the order they first appear
+fn pipe_fragments(pipe_input: &[Vec<Pipe>]) -> Vec<Vec<(i32, i32)>> {
+    let linked = |location: (i32, i32), dir: Direction| {
+        let (from, to) = (pipe_at(pipe_input, location), pipe_at(pipe_input, dir.get_loc(location)));
+        from.is_some_and(|pipe| pipe == Pipe::Start || pipe.connects_to(dir))
+            && to.is_some_and(|pipe| pipe == Pipe::Start || pipe.connects_to(dir.opposite()))
+    };
+
+    let mut seen = HashSet::new();
+    let mut fragments = vec![];
+    for (line, pipe_row) in pipe_input.iter().enumerate() {
+        for (col, &pipe) in pipe_row.iter().enumerate() {
+            let location = (line as i32, col as i32);
+            if pipe == Pipe::Ground || !seen.insert(location) {
+                continue;
+            }
+            let mut fragment = vec![location];
+            let mut to_visit = VecDeque::from([location]);
+            while let Some(location) = to_visit.pop_front() {
+                for dir in ALL_DIRECTIONS {
+                    let neighbor = dir.get_loc(location);
+                    if linked(location, dir) && seen.insert(neighbor) {
+                        fragment.push(neighbor);
+                        to_visit.push_back(neighbor);
+                    }
+                }
+            }
+            fragments.push(fragment);
+        }
+    }
+    fragments
+}
+
+// everything that keeps the map from being a single loop through S, ordered by what it concerns:
+// the start tile, then the pipes attached to it, then the unattached fragments
+fn validate(pipe_input: &[Vec<Pipe>]) -> Vec<PipeDiagnostic> {
+    let start = match find_starts(pipe_input).as_slice() {
+        [] => return vec![PipeDiagnostic::NoStart],
+        &[start] => start,
+        locations => return vec![PipeDiagnostic::MultipleStarts { locations: locations.to_vec() }]
+    };
+    let mut diagnostics = vec![];
+
+    let connections = ALL_DIRECTIONS.into_iter().filter(|dir| {
+        pipe_at(pipe_input, dir.get_loc(start)).is_some_and(|pipe| pipe.connects_to(dir.opposite()))
+    }).collect_vec();
+    if connections.len() != 2 {
+        diagnostics.push(PipeDiagnostic::AmbiguousStart { location: start, connections: connections.clone() });
+    }
+
+    // each loop gets walked once from either end, so the shape S would need is the pair of directions
+    let shapes = connections.iter().filter_map(|&dir| {
+        let path = walk_from(pipe_input, start, dir).ok()?;
+        let last = *path.last().unwrap();
+        let back = ALL_DIRECTIONS.into_iter().find(|dir| dir.get_loc(start) == last).unwrap();
+        Pipe::try_from(HashSet::from([dir, back])).ok()
+    }).unique().collect_vec();
+    if shapes.len() > 1 {
+        diagnostics.push(PipeDiagnostic::MultipleLoops { location: start, shapes });
+    }
+
+    for fragment in pipe_fragments(pipe_input) {
+        if fragment.contains(&start) {
+            for &location in &fragment {
+                let pipe = pipe_at(pipe_input, location).unwrap();
+                for direction in pipe.vec_neighbors().into_iter().flatten().sorted_by_key(|&dir| dir as u8) {
+                    let neighbor = pipe_at(pipe_input, direction.get_loc(location));
+                    if !neighbor.is_some_and(|pipe| pipe == Pipe::Start || pipe.connects_to(direction.opposite())) {
+                        diagnostics.push(PipeDiagnostic::DanglingEnd { location, direction });
+                    }
+                }
+            }
+        } else {
+            let closed = fragment.iter().all(|&location| {
+                pipe_at(pipe_input, location).unwrap().vec_neighbors().unwrap().into_iter().all(|dir| {
+                    pipe_at(pipe_input, dir.get_loc(location)).is_some_and(|pipe| pipe.connects_to(dir.opposite()))
+                })
+            });
+            diagnostics.push(PipeDiagnostic::DisconnectedFragment { location: fragment[0], tiles: fragment.len(), closed });
+        }
+    }
+
+    diagnostics
+}
+
+// clears every tile that isn't part of the main loop, as long as there is exactly one loop through S
+fn repair(pipe_input: &[Vec<Pipe>]) -> Option<Vec<Vec<Pipe>>> {
+    let &[start] = find_starts(pipe_input).as_slice() else {
+        return None;
+    };
+    let loops = ALL_DIRECTIONS.into_iter().filter_map(|dir| walk_from(pipe_input, start, dir).ok())
+        .map(|path| path.into_iter().collect::<HashSet<_>>())
+        .unique_by(|tiles| tiles.iter().copied().sorted().collect_vec())
+        .collect_vec();
+    let [main_loop] = loops.as_slice() else {
+        return None;
+    };
+
+    Some(pipe_input.iter().enumerate().map(|(line, pipe_row)| {
+        pipe_row.iter().enumerate().map(|(col, &pipe)| {
+            if main_loop.contains(&(line as i32, col as i32)) { pipe } else { Pipe::Ground }
+        }).collect()
+    }).collect())
+}
+
+#[aoc(day10, part1, validate)]
+fn part1_validate(input: &str) -> DiagnosticsReport<PipeDiagnostic> {
+    DiagnosticsReport(validate(&parse(input)))
+}
+
+// the map with everything but the main loop cleared away
+#[aoc(day10, part1, repaired)]
+fn part1_repaired(input: &str) -> String {
+    repair(&parse(input)).map_or("no single loop through S to keep".to_string(), |repaired| format!("\n{}", render(&repaired)))
+}
+
+impl Direction {
+    fn bit(&self) -> u8 {
+        match *self {
+            Direction::North => 1,
+            Direction::East => 2,
+            Direction::South => 4,
+            Direction::West => 8,
+        }
+    }
+}
+
+// a tile's code is the set of directions it connects to, plus flags for S and for being on the loop
+const START: u8 = 16;
+const ON_LOOP: u8 = 32;
+
+impl From<Pipe> for u8 {
+    fn from(pipe: Pipe) -> Self {
+        match pipe {
+            Pipe::Start => START,
+            _ => ALL_DIRECTIONS.into_iter().filter(|&dir| pipe.connects_to(dir)).fold(0, |code, dir| code | dir.bit())
+        }
+    }
+}
+
+// one byte per tile, row-major, so the map's size doesn't have to be square or fit a hash map
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct TileMap {
+    width: usize,
+    height: usize,
+    tiles: Vec<u8>,
+}
+impl TileMap {
+    fn parse(input: &str) -> Self {
+        // only eight characters are valid, so work their codes out once rather than for every tile
+        let mut codes = [None; 128];
+        for chr in "|-LJ7F.S".chars() {
+            codes[chr as usize] = Some(u8::from(Pipe::try_from(chr).unwrap()));
+        }
+
+        let width = input.lines().next().unwrap().len();
+        let mut tiles = Vec::with_capacity(input.len());
+        let mut height = 0;
+        for line in input.lines() {
+            assert_eq!(line.len(), width, "line {} is not as wide as the first", height + 1);
+            tiles.extend(line.bytes().map(|byte| codes.get(byte as usize).copied().flatten().expect("Invalid pipe")));
+            height += 1;
+        }
+        Self { width, height, tiles }
+    }
+
+    fn step(&self, index: usize, dir: Direction) -> Option<usize> {
+        let (line, col) = (index / self.width, index % self.width);
+        match dir {
+            Direction::North => (line > 0).then(|| index - self.width),
+            Direction::East => (col + 1 < self.width).then_some(index + 1),
+            Direction::South => (line + 1 < self.height).then_some(index + self.width),
+            Direction::West => (col > 0).then(|| index - 1),
+        }
+    }
+
+    fn connected(&self, index: usize, dir: Direction) -> Option<usize> {
+        self.step(index, dir).filter(|&next| self.tiles[next] & dir.opposite().bit() != 0)
+    }
+
+    // gives S the shape of whatever connects to it, then walks the loop flagging every tile on it,
+    // returning the loop's length
+    fn mark_loop(&mut self) -> usize {
+        let start = self.tiles.iter().position(|&tile| tile & START != 0).unwrap();
+        let shape = ALL_DIRECTIONS.into_iter().filter(|&dir| self.connected(start, dir).is_some())
+            .fold(0, |code, dir| code | dir.bit());
+        self.tiles[start] |= shape;
+
+        let mut dir = ALL_DIRECTIONS.into_iter().find(|dir| shape & dir.bit() != 0).unwrap();
+        let mut index = start;
+        let mut length = 0;
+        loop {
+            self.tiles[index] |= ON_LOOP;
+            length += 1;
+            index = self.connected(index, dir).unwrap();
+            if index == start {
+                return length;
+            }
+            let came_from = dir.opposite();
+            dir = ALL_DIRECTIONS.into_iter().find(|&next_dir| next_dir != came_from && self.tiles[index] & next_dir.bit() != 0).unwrap();
+        }
+    }
+
+    // scanning each row, the loop is crossed every time a loop tile reaches north
+    fn enclosed(&self) -> usize {
+        self.tiles.chunks(self.width).map(|row| {
+            row.iter().fold((0, false), |(count, inside), &tile| {
+                if tile & ON_LOOP == 0 {
+                    (count + inside as usize, inside)
+                } else {
+                    (count, inside ^ (tile & Direction::North.bit() != 0))
+                }
+            }).0
+        }).sum()
+    }
+}
+
+#[aoc(day10, part1, dense)]
+fn part1_dense(input: &str) -> usize {
+    let mut tiles = TileMap::parse(input);
+
+    tiles.mark_loop() / 2
+}
+
+#[aoc(day10, part2, dense)]
+fn part2_dense(input: &str) -> usize {
+    let mut tiles = TileMap::parse(input);
+    tiles.mark_loop();
+
+    tiles.enclosed()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_rng::TestRng;
 
     #[test]
     fn part1_example() {
@@ -360,4 +668,367 @@ L.L7LFJ|||||FJL7||LJ
 L7JLJL-JLJLJL--JLJ.L"), 10);
     }
 
-}
\ No newline at end of file
+    #[test]
+    fn part2_shoelace_example() {
+        assert_eq!(part2_shoelace("...........
+.S-------7.
+.|F-----7|.
+.||.....||.
+.||.....||.
+.|L-7.F-J|.
+.|..|.|..|.
+.L--J.L--J.
+..........."), 4);
+        assert_eq!(part2_shoelace(".F----7F7F7F7F-7....
+.|F--7||||||||FJ....
+.||.FJ||||||||L7....
+FJL7L7LJLJ||LJ.L-7..
+L--J.L7...LJS7F-7L7.
+....F-J..F7FJ|L7L7L7
+....L7.F7||L7|.L7L7|
+.....|FJLJ|FJ|F7|.LJ
+....FJL-7.||.||||...
+....L---J.LJ.LJLJ..."), 8);
+        assert_eq!(part2_shoelace("FF7FSF7F7F7F7F7F---7
+L|LJ||||||||||||F--J
+FL-7LJLJ||||||LJL-77
+F--JF--7||LJLJ7F7FJ-
+L---JF-JLJ.||-FJLJJ7
+|F|F-JF---7F7-L7L|7|
+|FFJF7L7F-JF7|JL---7
+7-L-JL7||F7|L7F-7F7|
+L.L7LFJ|||||FJL7||LJ
+L7JLJL-JLJLJL--JLJ.L"), 10);
+    }
+
+    // grows a random polyomino one cell at a time, keeping only cells that leave its outline a single
+    // simple loop, then draws that outline with pipes on the lattice points and fills the rest with junk
+    fn generate_loop(size: usize, seed: u64) -> String {
+        let mut rng = TestRng::new(seed);
+
+        // lattice point -> directions of outline edges leaving it
+        let outline = |cells: &HashSet<(usize, usize)>| {
+            let mut edges: HashMap<(usize, usize), Vec<Direction>> = HashMap::new();
+            for &(line, col) in cells {
+                let sides = [
+                    ((line.wrapping_sub(1), col), (line, col), (line, col + 1), Direction::East),
+                    ((line + 1, col), (line + 1, col), (line + 1, col + 1), Direction::East),
+                    ((line, col.wrapping_sub(1)), (line, col), (line + 1, col), Direction::South),
+                    ((line, col + 1), (line, col + 1), (line + 1, col + 1), Direction::South),
+                ];
+                for (neighbor, from, to, dir) in sides {
+                    if !cells.contains(&neighbor) {
+                        edges.entry(from).or_default().push(dir);
+                        edges.entry(to).or_default().push(dir.opposite());
+                    }
+                }
+            }
+            edges
+        };
+        let is_simple_loop = |cells: &HashSet<(usize, usize)>| {
+            let edges = outline(cells);
+            edges.values().all(|dirs| dirs.len() == 2)
+                && edges.values().map(|dirs| dirs.len()).sum::<usize>() / 2 == edges.len()
+                && {
+                    // walking the outline from any point has to visit every point
+                    let start = *edges.keys().next().unwrap();
+                    let mut dir = edges[&start][0];
+                    let mut point = start;
+                    let mut visited = 0;
+                    loop {
+                        let (line, col) = dir.get_loc((point.0 as i32, point.1 as i32));
+                        point = (line as usize, col as usize);
+                        visited += 1;
+                        if point == start {
+                            break;
+                        }
+                        dir = *edges[&point].iter().find(|&&next| next != dir.opposite()).unwrap();
+                    }
+                    visited == edges.len()
+                }
+        };
+
+        let mut cells = HashSet::from([(size / 2, size / 2)]);
+        for _ in 0..size * size {
+            let &(line, col) = cells.iter().nth(rng.below(cells.len())).unwrap();
+            let dir = ALL_DIRECTIONS[rng.below(4)];
+            let (line, col) = dir.get_loc((line as i32, col as i32));
+            if line < 1 || col < 1 || line >= size as i32 - 1 || col >= size as i32 - 1 {
+                continue;
+            }
+            let candidate = (line as usize, col as usize);
+            if cells.insert(candidate) && !is_simple_loop(&cells) {
+                cells.remove(&candidate);
+            }
+        }
+
+        let edges = outline(&cells);
+        let start = *edges.keys().nth(rng.below(edges.len())).unwrap();
+        let junk = ['|', '-', 'L', 'J', '7', 'F', '.', '.'];
+        (0..=size).map(|line| (0..=size).map(|col| {
+            match edges.get(&(line, col)) {
+                _ if (line, col) == start => 'S',
+                Some(dirs) => match Pipe::try_from(dirs.iter().copied().collect::<HashSet<_>>()).unwrap() {
+                    Pipe::Vertical => '|',
+                    Pipe::Horizontal => '-',
+                    Pipe::NorthEast => 'L',
+                    Pipe::NorthWest => 'J',
+                    Pipe::SouthWest => '7',
+                    Pipe::SouthEast => 'F',
+                    _ => unreachable!()
+                },
+                // leave S with exactly two pipes pointing at it
+                None if line.abs_diff(start.0) + col.abs_diff(start.1) == 1 => '.',
+                None => junk[rng.below(junk.len())]
+            }
+        }).collect::<String>()).join("\n")
+    }
+
+    #[test]
+    fn shoelace_matches_scan_on_generated_loops() {
+        for seed in 1..=40 {
+            let input = generate_loop(12 + seed as usize % 10, seed * 0x9E37_79B9);
+            assert_eq!(part2_shoelace(&input), part2(&input), "\n{input}");
+        }
+    }
+
+    #[test]
+    fn validate_example() {
+        assert_eq!(validate(&parse(".....
+.S-7.
+.|.|.
+.L-J.
+.....")), vec![]);
+
+        let diagnostics = validate(&parse("-L|F7
+7S-7|
+L|7||
+-L-J|
+L|-JF"));
+        assert_eq!(diagnostics.len(), 11);
+        assert_eq!(diagnostics[3], PipeDiagnostic::DisconnectedFragment { location: (0, 3), tiles: 5, closed: false });
+        assert_eq!(diagnostics[4], PipeDiagnostic::DisconnectedFragment { location: (1, 0), tiles: 2, closed: false });
+        assert_eq!(diagnostics[3].to_string(), "1:4: open fragment of 5 pipes apart from S");
+    }
+
+    #[test]
+    fn validate_problems() {
+        assert_eq!(validate(&parse("...\n.|.")), vec![PipeDiagnostic::NoStart]);
+        assert_eq!(validate(&parse("S-S")), vec![PipeDiagnostic::MultipleStarts { locations: vec![(0, 0), (0, 2)] }]);
+
+        let diagnostics = validate(&parse(".....
+.S-7.
+.|.|.
+.L-..
+....."));
+        assert_eq!(diagnostics, vec![
+            PipeDiagnostic::DanglingEnd { location: (2, 3), direction: Direction::South },
+            PipeDiagnostic::DanglingEnd { location: (3, 2), direction: Direction::East }
+        ]);
+        assert_eq!(diagnostics[1].to_string(), "4:3: pipe end to the east leads nowhere");
+
+        let diagnostics = validate(&parse(".F7
+FSJ
+LJ.
+..."));
+        assert_eq!(diagnostics, vec![
+            PipeDiagnostic::AmbiguousStart { location: (1, 1), connections: ALL_DIRECTIONS.to_vec() },
+            PipeDiagnostic::MultipleLoops { location: (1, 1), shapes: vec![Pipe::NorthEast, Pipe::SouthWest] }
+        ]);
+        assert_eq!(diagnostics[0].to_string(), "2:2: S connects north, east, south, west, expected exactly two pipes");
+        assert_eq!(diagnostics[1].to_string(), "2:2: S closes a loop as any of L, 7");
+
+        assert_eq!(validate(&parse("F7.
+LJ.
+..S")), vec![
+            PipeDiagnostic::AmbiguousStart { location: (2, 2), connections: vec![] },
+            PipeDiagnostic::DisconnectedFragment { location: (0, 0), tiles: 4, closed: true }
+        ]);
+    }
+
+    #[test]
+    fn repair_example() {
+        let repaired = repair(&parse("-L|F7
+7S-7|
+L|7||
+-L-J|
+L|-JF")).unwrap();
+        assert_eq!(render(&repaired), ".....
+.S-7.
+.|.|.
+.L-J.
+.....");
+
+        // the spur off S is cleared along with the rest of the junk
+        assert_eq!(render(&repair(&parse("..|..
+.FS7.
+.L-J.")).unwrap()), ".....
+.FS7.
+.L-J.");
+
+        assert_eq!(repair(&parse(".F7\nFSJ\nLJ.")), None);
+        assert_eq!(repair(&parse(".S-7.\n.|.|.\n.L-..")), None);
+    }
+
+    #[test]
+    fn part1_reports() {
+        let input = "..|..\n.FS7.\n.L-J.";
+        assert_eq!(part1_validate(input).to_string(), "\n2:3: S connects north, east, west, expected exactly two pipes\n1:3: pipe end to the north leads nowhere");
+        assert_eq!(part1_repaired(input), "\n.....\n.FS7.\n.L-J.");
+        assert_eq!(part1_validate(".....\n.FS7.\n.L-J.").to_string(), "no problems found");
+        assert_eq!(part1_repaired(".F7\nFSJ\nLJ."), "no single loop through S to keep");
+    }
+
+    #[test]
+    fn repair_keeps_generated_loops() {
+        for seed in 1..=20 {
+            let input = generate_loop(12 + seed as usize % 10, seed * 0x9E37_79B9);
+            let pipe_input = parse(&input);
+            assert!(validate(&pipe_input).iter().all(|diagnostic| matches!(diagnostic, PipeDiagnostic::DisconnectedFragment { .. })), "\n{input}");
+
+            let repaired = render(&repair(&pipe_input).unwrap());
+            assert_eq!(validate(&parse(&repaired)), vec![], "\n{repaired}");
+            assert_eq!(part2(&repaired), part2(&input), "\n{repaired}");
+        }
+    }
+
+    #[test]
+    fn part1_dense_example() {
+        assert_eq!(part1_dense(".....
+.S-7.
+.|.|.
+.L-J.
+....."), 4);
+        assert_eq!(part1_dense("..F7.
+.FJ|.
+SJ.L7
+|F--J
+LJ..."), 8);
+    }
+
+    #[test]
+    fn part2_dense_example() {
+        assert_eq!(part2_dense("...........
+.S-------7.
+.|F-----7|.
+.||.....||.
+.||.....||.
+.|L-7.F-J|.
+.|..|.|..|.
+.L--J.L--J.
+..........."), 4);
+        assert_eq!(part2_dense(".F----7F7F7F7F-7....
+.|F--7||||||||FJ....
+.||.FJ||||||||L7....
+FJL7L7LJLJ||LJ.L-7..
+L--J.L7...LJS7F-7L7.
+....F-J..F7FJ|L7L7L7
+....L7.F7||L7|.L7L7|
+.....|FJLJ|FJ|F7|.LJ
+....FJL-7.||.||||...
+....L---J.LJ.LJLJ..."), 8);
+        assert_eq!(part2_dense("FF7FSF7F7F7F7F7F---7
+L|LJ||||||||||||F--J
+FL-7LJLJ||||||LJL-77
+F--JF--7||LJLJ7F7FJ-
+L---JF-JLJ.||-FJLJJ7
+|F|F-JF---7F7-L7L|7|
+|FFJF7L7F-JF7|JL---7
+7-L-JL7||F7|L7F-7F7|
+L.L7LFJ|||||FJL7||LJ
+L7JLJL-JLJLJL--JLJ.L"), 10);
+    }
+
+    #[test]
+    fn dense_matches_scan_on_generated_loops() {
+        for seed in 1..=40 {
+            let input = generate_loop(12 + seed as usize % 10, seed * 0x9E37_79B9);
+            assert_eq!(part1_dense(&input), part1(&input) as usize, "\n{input}");
+            assert_eq!(part2_dense(&input), part2(&input) as usize, "\n{input}");
+        }
+    }
+
+    // a comb-shaped loop: along the top, down the right edge, then back along the bottom with teeth of
+    // random widths and heights reaching up into the middle. returns the map along with the loop's
+    // length and the tiles it encloses, worked out from its corners with the shoelace formula
+    fn generate_comb(height: usize, width: usize, seed: u64) -> (String, usize, usize) {
+        let mut rng = TestRng::new(seed);
+
+        let mut corners = vec![(0, 0), (0, width - 1), (height - 1, width - 1)];
+        let mut col = width - 1;
+        loop {
+            let gap = 1 + rng.below(3);
+            if col < gap + 2 {
+                break;
+            }
+            let top = 2 + rng.below(height - 3);
+            corners.extend([(height - 1, col - gap), (top, col - gap), (top, col - gap - 1), (height - 1, col - gap - 1)]);
+            col -= gap + 1;
+        }
+        corners.push((height - 1, 0));
+
+        let mut codes = vec![0u8; height * width];
+        let mut length = 0;
+        for (&(line, col), &(next_line, next_col)) in corners.iter().circular_tuple_windows() {
+            let dir = match (next_line.cmp(&line), next_col.cmp(&col)) {
+                (std::cmp::Ordering::Less, _) => Direction::North,
+                (std::cmp::Ordering::Greater, _) => Direction::South,
+                (_, std::cmp::Ordering::Greater) => Direction::East,
+                _ => Direction::West
+            };
+            let mut at = (line as i32, col as i32);
+            while at != (next_line as i32, next_col as i32) {
+                codes[at.0 as usize * width + at.1 as usize] |= dir.bit();
+                at = dir.get_loc(at);
+                codes[at.0 as usize * width + at.1 as usize] |= dir.opposite().bit();
+                length += 1;
+            }
+        }
+        let twice_area = corners.iter().circular_tuple_windows().map(|(&(line, col), &(next_line, next_col))| {
+            line as i64 * next_col as i64 - next_line as i64 * col as i64
+        }).sum::<i64>().unsigned_abs() as usize;
+        let enclosed = (twice_area - length) / 2 + 1;
+
+        let mut chars = ['.'; 16];
+        for pipe in [Pipe::Vertical, Pipe::Horizontal, Pipe::NorthEast, Pipe::NorthWest, Pipe::SouthWest, Pipe::SouthEast] {
+            chars[u8::from(pipe) as usize] = char::from(pipe);
+        }
+        let start = (0, 1 + rng.below(width - 2));
+        let junk = ['|', '-', 'L', 'J', '7', 'F', '.', '.'];
+        let mut input = String::with_capacity((width + 1) * height);
+        for line in 0..height {
+            for col in 0..width {
+                input.push(match codes[line * width + col] {
+                    _ if (line, col) == start => 'S',
+                    // leave S with exactly two pipes pointing at it
+                    0 if line == 1 && col.abs_diff(start.1) <= 1 => '.',
+                    0 => junk[rng.below(junk.len())],
+                    code => chars[code as usize]
+                });
+            }
+            input.push('\n');
+        }
+        (input, length, enclosed)
+    }
+
+    #[test]
+    fn dense_non_square() {
+        for (seed, (height, width)) in [(5, 300), (300, 5), (57, 1003), (1003, 57), (4, 4), (13, 8)].into_iter().enumerate() {
+            let (input, length, enclosed) = generate_comb(height, width, seed as u64 + 1);
+            assert_eq!(part1_dense(&input), length / 2, "\n{input}");
+            assert_eq!(part2_dense(&input), enclosed, "\n{input}");
+            assert_eq!(part2_shoelace(&input) as usize, enclosed, "\n{input}");
+        }
+    }
+
+    #[test]
+    #[ignore = "10k by 10k map, slow in debug builds, run with --ignored"]
+    fn dense_huge() {
+        let (input, length, enclosed) = generate_comb(10_000, 10_000, 0x2023_1210);
+        let mut tiles = TileMap::parse(&input);
+        assert_eq!((tiles.height, tiles.width), (10_000, 10_000));
+        assert_eq!(tiles.mark_loop(), length);
+        assert_eq!(tiles.enclosed(), enclosed);
+    }
+
+}
@@ -1,5 +1,5 @@
 
-use std::collections::{HashSet, HashMap};
+use std::{collections::{HashSet, HashMap, BTreeMap}, fmt::Display};
 
 use itertools::Itertools;
 use aoc_runner_derive::aoc;
@@ -165,9 +165,209 @@ fn part2(input: &str) -> u64 {
 }
 
 
+fn galaxy_locations(image: &[Vec<ImagePixel>]) -> Vec<(u64, u64)> {
+    image.iter().enumerate().flat_map(|(row_ix, row)| {
+        row.iter().positions(|&pixel| pixel == ImagePixel::Galaxy).map(move |col_ix| (row_ix as u64, col_ix as u64))
+    }).collect()
+}
+
+// how wide the lines without galaxies become along one axis
+trait LineExpansion {
+    // the expanded width of the empty lines from `start` up to but not including `end`
+    fn empty_width(&self, start: u64, end: u64) -> u128;
+}
+
+// every empty line becomes this many lines
+impl LineExpansion for u64 {
+    fn empty_width(&self, start: u64, end: u64) -> u128 {
+        (end - start) as u128 * *self as u128
+    }
+}
+
+// each empty line gets its own width, looked up by its index in the original image
+struct PerLine<F: Fn(u64) -> u64>(F);
+impl<F: Fn(u64) -> u64> LineExpansion for PerLine<F> {
+    fn empty_width(&self, start: u64, end: u64) -> u128 {
+        (start..end).map(|line| (self.0)(line) as u128).sum()
+    }
+}
+
+// expanded positions along one axis, in the same order as `coords`. only the lines something sits on
+// keep their width, so walking them in order is enough to know what lies in each gap
+fn expand_axis(coords: &[u64], expansion: &impl LineExpansion) -> Vec<u128> {
+    let lines = coords.iter().copied().sorted_unstable().dedup().collect_vec();
+    let mut next_line = 0;
+    let mut next_pos = 0;
+    let positions = lines.iter().map(|&line| {
+        let pos = next_pos + expansion.empty_width(next_line, line);
+        next_line = line + 1;
+        next_pos = pos + 1;
+        pos
+    }).collect_vec();
+
+    coords.iter().map(|coord| positions[lines.binary_search(coord).unwrap()]).collect()
+}
+
+// with positions sorted, each one is the far end of a pair with every position before it
+fn pairwise_axis_sum(sorted: &[u128]) -> u128 {
+    let mut prefix = 0;
+    sorted.iter().enumerate().map(|(ix, &pos)| {
+        let sum = pos * ix as u128 - prefix;
+        prefix += pos;
+        sum
+    }).sum()
+}
+
+// galaxies at their expanded locations, indexed the same as the galaxies they were built from
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ExpandedGalaxies {
+    locations: Vec<(u128, u128)>
+}
+impl ExpandedGalaxies {
+    fn new(galaxies: &[(u64, u64)], row_expansion: &impl LineExpansion, col_expansion: &impl LineExpansion) -> Self {
+        let (rows, cols): (Vec<_>, Vec<_>) = galaxies.iter().copied().unzip();
+        let locations = expand_axis(&rows, row_expansion).into_iter().zip(expand_axis(&cols, col_expansion)).collect();
+        Self { locations }
+    }
+
+    fn distance(&self, a: usize, b: usize) -> u128 {
+        let ((a_row, a_col), (b_row, b_col)) = (self.locations[a], self.locations[b]);
+        a_row.abs_diff(b_row) + a_col.abs_diff(b_col)
+    }
+
+    // manhattan distances split per axis, so the total over all pairs only needs each axis sorted
+    fn sum_of_distances(&self) -> u128 {
+        let (rows, cols): (Vec<_>, Vec<_>) = self.locations.iter().copied().unzip();
+        pairwise_axis_sum(&rows.into_iter().sorted_unstable().collect_vec())
+            + pairwise_axis_sum(&cols.into_iter().sorted_unstable().collect_vec())
+    }
+
+    // the `k` other galaxies closest to `galaxy`, nearest first with ties going to the lower index
+    fn k_nearest(&self, galaxy: usize, k: usize) -> Vec<(usize, u128)> {
+        let mut others = (0..self.locations.len()).filter(|&other| other != galaxy)
+            .map(|other| (other, self.distance(galaxy, other)))
+            .collect_vec();
+        let by_distance = |&(other, distance): &(usize, u128)| (distance, other);
+        if k < others.len() {
+            others.select_nth_unstable_by_key(k, by_distance);
+            others.truncate(k);
+        }
+        others.sort_unstable_by_key(by_distance);
+        others
+    }
+
+    // rotating by 45 degrees turns manhattan distance into the larger of the two axis distances, so
+    // the farthest pair sits at the extremes of one of the rotated axes
+    fn farthest_pair(&self) -> Option<(usize, usize, u128)> {
+        let rotated = self.locations.iter().map(|&(row, col)| (row as i128 + col as i128, row as i128 - col as i128)).collect_vec();
+        let extremes = |axis: fn(&(i128, i128)) -> i128| {
+            let (min, max) = (0..rotated.len()).minmax_by_key(|&ix| axis(&rotated[ix])).into_option()?;
+            Some((min, max, axis(&rotated[max]).abs_diff(axis(&rotated[min]))))
+        };
+        let sums = extremes(|&(sum, _)| sum)?;
+        let diffs = extremes(|&(_, diff)| diff)?;
+        let (a, b, distance) = if diffs.2 > sums.2 { diffs } else { sums };
+        (a != b).then_some((a.min(b), a.max(b), distance))
+    }
+
+    // how many pairs fall into each `bucket_width` wide range of distances, keyed by where the range
+    // starts. unlike the sum this has to look at every pair
+    fn distance_histogram(&self, bucket_width: u128) -> BTreeMap<u128, u64> {
+        (0..self.locations.len()).tuple_combinations().fold(BTreeMap::new(), |mut histogram, (a, b)| {
+            let distance = self.distance(a, b);
+            *histogram.entry(distance - distance % bucket_width).or_default() += 1;
+            histogram
+        })
+    }
+}
+
+fn sum_of_distances(galaxies: &[(u64, u64)], expansion_factor: u64) -> u128 {
+    ExpandedGalaxies::new(galaxies, &expansion_factor, &expansion_factor).sum_of_distances()
+}
+
+#[aoc(day11, part1, sparse)]
+fn part1_sparse(input: &str) -> u128 {
+    let input = parse(input);
+    sum_of_distances(&galaxy_locations(&input), 2)
+}
+
+#[aoc(day11, part2, sparse)]
+fn part2_sparse(input: &str) -> u128 {
+    let input = parse(input);
+    sum_of_distances(&galaxy_locations(&input), 1_000_000)
+}
+
+// galaxies are numbered from one in the report, like in the puzzle text
+#[derive(Debug)]
+struct GalaxyReport {
+    galaxies: usize,
+    total_distance: u128,
+    closest_pair: Option<(usize, usize, u128)>,
+    farthest_pair: Option<(usize, usize, u128)>,
+    bucket_width: u128,
+    histogram: BTreeMap<u128, u64>
+}
+impl From<ExpandedGalaxies> for GalaxyReport {
+    fn from(expanded: ExpandedGalaxies) -> Self {
+        let closest_pair = (0..expanded.locations.len())
+            .filter_map(|galaxy| expanded.k_nearest(galaxy, 1).first().map(|&(other, distance)| (galaxy.min(other), galaxy.max(other), distance)))
+            .min_by_key(|&(a, b, distance)| (distance, a, b));
+        let farthest_pair = expanded.farthest_pair();
+        // ten buckets spanning everything up to the farthest pair
+        let bucket_width = farthest_pair.map_or(1, |(_, _, distance)| distance / 10 + 1);
+        Self {
+            galaxies: expanded.locations.len(),
+            total_distance: expanded.sum_of_distances(),
+            closest_pair,
+            farthest_pair,
+            bucket_width,
+            histogram: expanded.distance_histogram(bucket_width)
+        }
+    }
+}
+impl Display for GalaxyReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let pair = |pair: Option<(usize, usize, u128)>| pair.map_or("-".to_string(), |(a, b, distance)| format!("{} and {}, {distance} apart", a + 1, b + 1));
+        writeln!(f)?;
+        writeln!(f, "galaxies: {}", self.galaxies)?;
+        writeln!(f, "closest pair: {}", pair(self.closest_pair))?;
+        writeln!(f, "farthest pair: {}", pair(self.farthest_pair))?;
+        writeln!(f)?;
+        writeln!(f, "pairs by distance, in buckets of {}:", self.bucket_width)?;
+        let most = self.histogram.values().copied().max().unwrap_or(1);
+        for (&bucket, &count) in &self.histogram {
+            writeln!(f, "{:>16}  {:>8}  {}", format!("{bucket}.."), count, "#".repeat((count * 50 / most) as usize))?;
+        }
+        writeln!(f)?;
+        write!(f, "sum of distances: {}", self.total_distance)
+    }
+}
+
+#[aoc(day11, part1, report)]
+fn part1_report(input: &str) -> GalaxyReport {
+    let input = parse(input);
+    GalaxyReport::from(ExpandedGalaxies::new(&galaxy_locations(&input), &2, &2))
+}
+
+#[aoc(day11, part2, report)]
+fn part2_report(input: &str) -> GalaxyReport {
+    let input = parse(input);
+    GalaxyReport::from(ExpandedGalaxies::new(&galaxy_locations(&input), &1_000_000, &1_000_000))
+}
+
+// an uneven universe, where rows further down have been expanding for longer: an empty row becomes
+// one line more than its index, while every empty column still becomes a million
+#[aoc(day11, part2, per_axis)]
+fn part2_per_axis(input: &str) -> u128 {
+    let input = parse(input);
+    ExpandedGalaxies::new(&galaxy_locations(&input), &PerLine(|row| row + 1), &1_000_000).sum_of_distances()
+}
+
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_rng::TestRng;
 
     #[test]
     fn part1_example() {
@@ -217,4 +417,149 @@ mod tests {
 #...#....."), 100), 8410);
     }
 
-}
\ No newline at end of file
+    const EXAMPLE: &str = "...#......
+.......#..
+#.........
+..........
+......#...
+.#........
+.........#
+..........
+.......#..
+#...#.....";
+
+    #[test]
+    fn sparse_example() {
+        assert_eq!(part1_sparse(EXAMPLE), 374);
+        let galaxies = galaxy_locations(&parse(EXAMPLE));
+        assert_eq!(sum_of_distances(&galaxies, 10), 1030);
+        assert_eq!(sum_of_distances(&galaxies, 100), 8410);
+    }
+
+    #[test]
+    fn expand_axis_test() {
+        assert_eq!(expand_axis(&[5, 1, 1, 3], &10), vec![32, 10, 10, 21]);
+        assert_eq!(expand_axis(&[5, 1, 1, 3], &1), vec![5, 1, 1, 3]);
+        // with no room for empty lines, galaxies pack together
+        assert_eq!(expand_axis(&[5, 1, 1, 3], &0), vec![2, 0, 0, 1]);
+        // empty lines 0, 2 and 4 become 0, 2 and 4 lines wide
+        assert_eq!(expand_axis(&[5, 1, 1, 3], &PerLine(|line| line)), vec![8, 0, 0, 3]);
+        assert_eq!(pairwise_axis_sum(&[1, 1, 3, 5]), 2 + 2 + 4 + 4 + 2);
+    }
+
+    #[test]
+    fn sparse_matches_pairwise() {
+        let mut rng = TestRng::new(0x2023_1211);
+        for _ in 0..50 {
+            let (rows, cols) = (1 + rng.below(30), 1 + rng.below(30));
+            let image = rng.grid(rows, cols, |rng| {
+                if rng.below(10) == 0 { ImagePixel::Galaxy } else { ImagePixel::Empty }
+            });
+            let galaxies = galaxy_locations(&image);
+            for expansion_factor in [1, 2, 7, 1_000_000] {
+                assert_eq!(
+                    sum_of_distances(&galaxies, expansion_factor),
+                    process_galaxy_image_with_expansion(image.clone(), expansion_factor) as u128
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn sparse_lattice() {
+        assert_lattice_distances(100);
+    }
+
+    #[test]
+    #[ignore = "benchmark, run with --ignored --nocapture"]
+    fn sparse_million_galaxies() {
+        let start = std::time::Instant::now();
+        assert_lattice_distances(1000);
+        println!("million galaxies: {:?}", start.elapsed());
+    }
+
+    // a side x side lattice of galaxies on every other line, so every gap is one empty line. along
+    // one axis each of the side values is shared by side galaxies, and the distinct values are
+    // (expansion_factor + 1) apart
+    fn assert_lattice_distances(side: u64) {
+        let galaxies = (0..side).cartesian_product(0..side).map(|(row, col)| (row * 2, col * 2)).collect_vec();
+        for expansion_factor in [0, 1, 2, 1_000_000, u32::MAX as u64] {
+            let side = side as u128;
+            let spacing = expansion_factor as u128 + 1;
+            let per_axis = side * side * spacing * (side * side * side - side) / 6;
+            assert_eq!(sum_of_distances(&galaxies, expansion_factor), 2 * per_axis);
+        }
+    }
+
+    #[test]
+    fn per_axis_expansion() {
+        let galaxies = galaxy_locations(&parse(EXAMPLE));
+        // stretching only one axis is the same as transposing the image and stretching the other
+        let transposed = galaxies.iter().map(|&(row, col)| (col, row)).collect_vec();
+        let rows_only = ExpandedGalaxies::new(&galaxies, &100, &1);
+        let cols_only = ExpandedGalaxies::new(&transposed, &1, &100);
+        assert_eq!(rows_only.sum_of_distances(), cols_only.sum_of_distances());
+        assert!(rows_only.sum_of_distances() < sum_of_distances(&galaxies, 100));
+        assert_eq!(
+            ExpandedGalaxies::new(&galaxies, &PerLine(|_| 10), &PerLine(|_| 10)),
+            ExpandedGalaxies::new(&galaxies, &10, &10)
+        );
+    }
+
+    #[test]
+    fn part2_per_axis_example() {
+        // empty rows 3 and 7 become 4 and 8 lines
+        assert_eq!(part2_per_axis(EXAMPLE), 46_000_426);
+    }
+
+    #[test]
+    fn galaxy_queries() {
+        let expanded = ExpandedGalaxies::new(&galaxy_locations(&parse(EXAMPLE)), &2, &2);
+        // galaxies 5 and 9 from the puzzle text, numbered from one there
+        assert_eq!(expanded.distance(4, 8), 9);
+        assert_eq!(expanded.k_nearest(4, 2), vec![(2, 5), (7, 6)]);
+        assert_eq!(expanded.farthest_pair(), Some((1, 7, 19)));
+        assert_eq!(expanded.distance_histogram(5), BTreeMap::from([(5, 18), (10, 11), (15, 7)]));
+
+        let single = ExpandedGalaxies::new(&[(3, 3)], &2, &2);
+        assert_eq!(single.farthest_pair(), None);
+        assert_eq!(single.k_nearest(0, 3), vec![]);
+    }
+
+    #[test]
+    fn part1_report_example() {
+        let report = part1_report(EXAMPLE);
+        assert_eq!((report.closest_pair, report.farthest_pair), (Some((1, 3, 5)), Some((1, 7, 19))));
+        assert_eq!(report.histogram.values().sum::<u64>(), 36);
+        assert!(report.to_string().ends_with("sum of distances: 374"));
+        assert_eq!(part2_report(EXAMPLE).total_distance, part2_sparse(EXAMPLE));
+    }
+
+    #[test]
+    fn galaxy_queries_match_brute_force() {
+        let mut rng = TestRng::new(0x2023_1211);
+        for _ in 0..30 {
+            let galaxies = (0..2 + rng.below(40)).map(|_| (rng.below(50) as u64, rng.below(50) as u64)).collect_vec();
+            let weight = |line: u64| line % 4;
+            let expanded = ExpandedGalaxies::new(&galaxies, &PerLine(weight), &3);
+
+            // expand by hand, one line at a time
+            let occupied = |line: u64, axis: fn(&(u64, u64)) -> u64| galaxies.iter().any(|galaxy| axis(galaxy) == line);
+            let brute = galaxies.iter().map(|&(row, col)| (
+                (0..row).map(|line| if occupied(line, |&(row, _)| row) { 1 } else { weight(line) as u128 }).sum::<u128>(),
+                (0..col).map(|line| if occupied(line, |&(_, col)| col) { 1 } else { 3 }).sum::<u128>()
+            )).collect_vec();
+            assert_eq!(expanded.locations, brute);
+
+            let pairs = (0..galaxies.len()).tuple_combinations().map(|(a, b)| (a, b, expanded.distance(a, b))).collect_vec();
+            assert_eq!(expanded.sum_of_distances(), pairs.iter().map(|&(_, _, distance)| distance).sum::<u128>());
+            assert_eq!(expanded.farthest_pair().unwrap().2, pairs.iter().map(|&(_, _, distance)| distance).max().unwrap());
+            assert_eq!(expanded.distance_histogram(7).values().sum::<u64>(), pairs.len() as u64);
+
+            let nearest = (1..galaxies.len()).map(|other| (other, expanded.distance(0, other)))
+                .sorted_by_key(|&(other, distance)| (distance, other)).take(3).collect_vec();
+            assert_eq!(expanded.k_nearest(0, 3), nearest);
+        }
+    }
+
+}
@@ -1,11 +1,15 @@
 
-use std::{iter::once, collections::HashMap};
+use std::collections::HashMap;
 // use rayon::prelude::*;
 
 use itertools::Itertools;
 use aoc_runner_derive::aoc;
 use nom::{IResult, combinator::{map_res, all_consuming, map}, character::complete::{line_ending, one_of, space1, digit1, char}, multi::{many1, separated_list1}, sequence::separated_pair};
 
+// not part of the puzzle, a solver for grids whose columns have rules too, built on the same
+// counting as the puzzle's rows
+mod nonogram;
+
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 enum Spring {
@@ -93,6 +97,9 @@ fn num_possibilities_memoized(input: SpringRow, memoized: &mut HashMap<SpringRow
     if rules.len() == 0 && cells.iter().all(|cell| cell.can_be(Spring::Operational)) {
         // println!("no cells and no rules");
         return 1;
+    } else if rules.is_empty() {
+        // a damaged spring is left over with no rule to account for it
+        return 0;
     }
 
     let min_remaining_cells = rules.iter().sum::<usize>() + rules.len() - 1;
@@ -174,24 +181,258 @@ fn part1(input: &str) -> u64 {
 fn part2(input: &str) -> u64 {
     let input = parse(input);
 
-    let input = input.into_iter().map(|row| {
-        let rules = row.rules.into_iter();
-        let new_rules = rules.clone().chain(rules.clone()).chain(rules.clone()).chain(rules.clone()).chain(rules);
-        let cells = row.springs.into_iter();
-        let cells_with_gap = cells.clone().chain(once(Spring::Unknown));
-        let new_cells = cells_with_gap.clone().chain(cells_with_gap.clone()).chain(cells_with_gap.clone()).chain(cells_with_gap).chain(cells);
-        (new_cells.collect_vec(), new_rules.collect_vec()).into()
-    }).collect_vec();
-    
     input.into_iter().fold(0, |sum, row| {
-        sum + num_possibilities(&row)
+        sum + num_possibilities(&row.unfold(5))
+    })
+}
+
+impl SpringRow {
+    // `factor` copies of the row joined by unknown springs, with the rules repeated to match
+    fn unfold(&self, factor: usize) -> SpringRow {
+        let springs = Itertools::intersperse(itertools::repeat_n(self.springs.clone(), factor), vec![Spring::Unknown]).flatten();
+        let rules = itertools::repeat_n(self.rules.iter().copied(), factor).flatten();
+        (springs.collect_vec(), rules.collect_vec()).into()
+    }
+}
+
+
+// `factor` copies of a row's springs joined by unknown springs, with the rules repeated to match,
+// read by index so unfolding a row doesn't have to build it
+#[derive(Debug, Clone, Copy)]
+struct UnfoldedRow<'a> {
+    springs: &'a [Spring],
+    rules: &'a [usize],
+    factor: usize
+}
+impl UnfoldedRow<'_> {
+    fn num_springs(&self) -> usize {
+        ((self.springs.len() + 1) * self.factor).saturating_sub(1)
+    }
+
+    fn num_rules(&self) -> usize {
+        self.rules.len() * self.factor
+    }
+
+    fn spring(&self, spring_ix: usize) -> Spring {
+        self.springs.get(spring_ix % (self.springs.len() + 1)).copied().unwrap_or(Spring::Unknown)
+    }
+
+    fn rule(&self, rule_ix: usize) -> Option<usize> {
+        (rule_ix < self.num_rules()).then(|| self.rules[rule_ix % self.rules.len()])
+    }
+}
+
+
+// bottom-up version of the recursion: ways[i][j] counts the arrangements of springs[i..] that satisfy
+// rules[j..]. the buffers are kept between rows so counting a whole input allocates only a handful
+// of times
+#[derive(Debug, Clone, Default)]
+struct SpringCounter {
+    ways: Vec<u128>,
+    // how many springs from each index on could all be damaged
+    damaged_runs: Vec<usize>,
+    num_rules: usize,
+}
+impl SpringCounter {
+    fn ways(&self, spring_ix: usize, rule_ix: usize) -> u128 {
+        self.ways[spring_ix * (self.num_rules + 1) + rule_ix]
+    }
+
+    // where the row picks up again if the run for rules[rule_ix] starts at spring_ix, which needs it
+    // to end at the last spring or an operational one
+    fn after_run(&self, row: &UnfoldedRow, spring_ix: usize, rule_ix: usize) -> Option<usize> {
+        let rule = row.rule(rule_ix)?;
+        let end = spring_ix + rule;
+        if self.damaged_runs[spring_ix] < rule {
+            None
+        } else if end == row.num_springs() {
+            Some(end)
+        } else {
+            row.spring(end).can_be(Spring::Operational).then_some(end + 1)
+        }
+    }
+
+    fn count(&mut self, springs: &[Spring], rules: &[usize]) -> u128 {
+        self.count_unfolded(&UnfoldedRow { springs, rules, factor: 1 })
+    }
+
+    fn count_unfolded(&mut self, row: &UnfoldedRow) -> u128 {
+        let (num_springs, num_rules) = (row.num_springs(), row.num_rules());
+        let width = num_rules + 1;
+        self.num_rules = num_rules;
+        self.ways.clear();
+        self.ways.resize((num_springs + 1) * width, 0);
+        self.damaged_runs.clear();
+        self.damaged_runs.resize(num_springs + 1, 0);
+
+        self.ways[num_springs * width + num_rules] = 1;
+        for spring_ix in (0..num_springs).rev() {
+            let spring = row.spring(spring_ix);
+            if spring.can_be(Spring::Damaged) {
+                self.damaged_runs[spring_ix] = self.damaged_runs[spring_ix + 1] + 1;
+            }
+            for rule_ix in 0..=num_rules {
+                let mut ways = 0;
+                if spring.can_be(Spring::Operational) {
+                    ways += self.ways[(spring_ix + 1) * width + rule_ix];
+                }
+                if let Some(next_ix) = self.after_run(row, spring_ix, rule_ix) {
+                    ways += self.ways(next_ix, rule_ix + 1);
+                }
+                self.ways[spring_ix * width + rule_ix] = ways;
+            }
+        }
+
+        self.ways(0, 0)
+    }
+}
+
+fn count_unfolded(rows: &[SpringRow], factor: usize) -> u128 {
+    let mut counter = SpringCounter::default();
+    rows.iter().map(|row| {
+        counter.count_unfolded(&UnfoldedRow { springs: &row.springs, rules: &row.rules, factor })
+    }).sum()
+}
+
+#[aoc(day12, part1, dp)]
+fn part1_dp(input: &str) -> u128 {
+    let input = parse(input);
+
+    count_unfolded(&input, 1)
+}
+
+#[aoc(day12, part2, dp)]
+fn part2_dp(input: &str) -> u128 {
+    let input = parse(input);
+
+    count_unfolded(&input, 5)
+}
+
+impl From<Spring> for char {
+    fn from(spring: Spring) -> Self {
+        match spring {
+            Spring::Operational => '.',
+            Spring::Damaged => '#',
+            Spring::Unknown => '?',
+        }
+    }
+}
+
+fn render(springs: &[Spring]) -> String {
+    springs.iter().map(|&spring| char::from(spring)).collect()
+}
+
+// every way of filling in a row's unknown springs, ordered as if operational sorts before damaged.
+// with the counts from the table any one of them can be built directly from its position, which
+// makes listing them lazy and sampling them uniform
+#[derive(Debug, Clone)]
+struct Arrangements {
+    row: SpringRow,
+    counter: SpringCounter,
+    total: u128,
+}
+impl Arrangements {
+    fn new(row: &SpringRow) -> Self {
+        let mut counter = SpringCounter::default();
+        let total = counter.count(&row.springs, &row.rules);
+        Self { row: row.clone(), counter, total }
+    }
+
+    fn nth(&self, mut index: u128) -> Option<Vec<Spring>> {
+        if index >= self.total {
+            return None;
+        }
+        let (springs, rules) = (&self.row.springs, &self.row.rules);
+        let row = UnfoldedRow { springs, rules, factor: 1 };
+        let mut arrangement = Vec::with_capacity(springs.len());
+        let (mut spring_ix, mut rule_ix) = (0, 0);
+        while spring_ix < springs.len() {
+            let operational = if springs[spring_ix].can_be(Spring::Operational) {
+                self.counter.ways(spring_ix + 1, rule_ix)
+            } else {
+                0
+            };
+            if index < operational {
+                arrangement.push(Spring::Operational);
+                spring_ix += 1;
+            } else {
+                index -= operational;
+                let next_ix = self.counter.after_run(&row, spring_ix, rule_ix).unwrap();
+                arrangement.extend(itertools::repeat_n(Spring::Damaged, rules[rule_ix]));
+                arrangement.extend(itertools::repeat_n(Spring::Operational, next_ix - spring_ix - rules[rule_ix]));
+                spring_ix = next_ix;
+                rule_ix += 1;
+            }
+        }
+        Some(arrangement)
+    }
+
+    fn iter(&self) -> impl DoubleEndedIterator<Item = Vec<Spring>> + '_ {
+        (0..self.total).map(|index| self.nth(index).unwrap())
+    }
+
+    // `next_random` supplies uniformly random bits; draws that would favour the lower indices are
+    // thrown away rather than taken modulo the total
+    fn sample(&self, mut next_random: impl FnMut() -> u64) -> Option<Vec<Spring>> {
+        if self.total == 0 {
+            return None;
+        }
+        let limit = u128::MAX - u128::MAX % self.total;
+        loop {
+            let random = (next_random() as u128) << 64 | next_random() as u128;
+            if random < limit {
+                return self.nth(random % self.total);
+            }
+        }
+    }
+}
+
+// each row's arrangements in order, first and last, along with one picked at random
+fn arrangements_report(rows: &[SpringRow], mut next_random: impl FnMut() -> u64) -> String {
+    let lines = rows.iter().enumerate().map(|(idx, row)| {
+        let arrangements = Arrangements::new(row);
+        let mut listed = arrangements.iter();
+        match (listed.next(), listed.next_back(), arrangements.sample(&mut next_random)) {
+            (Some(first), last, Some(sample)) => format!("line {}: {} arrangements, first {}, last {}, random {}", idx + 1, arrangements.total,
+                render(&first), render(last.as_ref().unwrap_or(&first)), render(&sample)),
+            _ => format!("line {}: no arrangements", idx + 1)
+        }
+    });
+    format!("\n{}", lines.collect_vec().join("\n"))
+}
+
+const ARRANGEMENT_SEED: u64 = 0x2023_1212;
+
+#[aoc(day12, part1, arrangements)]
+fn part1_arrangements(input: &str) -> String {
+    let input = parse(input);
+    // std has no random number generator, so draw from a splitmix64 stream over a fixed seed to
+    // pick the same arrangements on every run
+    let mut state = ARRANGEMENT_SEED;
+
+    arrangements_report(&input, || {
+        state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
     })
 }
 
+// the puzzle input isn't a nonogram, but an input laid out like one (row clues, a blank line,
+// then column clues) gets solved
+#[aoc(day12, part1, nonogram)]
+fn part1_nonogram(input: &str) -> String {
+    match nonogram::parse(input) {
+        Some(puzzle) => puzzle.solve().to_string(),
+        None => "input is not a nonogram".to_string(),
+    }
+}
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_rng::TestRng;
 
     #[test]
     fn part1_example() {
@@ -213,4 +454,158 @@ mod tests {
 ?###???????? 3,2,1"), 525152);
     }
 
+    const EXAMPLE: &str = "???.### 1,1,3
+.??..??...?##. 1,1,3
+?#?#?#?#?#?#?#? 1,3,1,6
+????.#...#... 4,1,1
+????.######..#####. 1,6,5
+?###???????? 3,2,1";
+
+    #[test]
+    fn unfold_test() {
+        assert_eq!(parse(".# 1")[0].unfold(5), parse(".#?.#?.#?.#?.# 1,1,1,1,1")[0]);
+        assert_eq!(parse("???.### 1,1,3")[0].unfold(1), parse("???.### 1,1,3")[0]);
+    }
+
+    #[test]
+    fn dp_example() {
+        assert_eq!(part1_dp(EXAMPLE), 21);
+        assert_eq!(part2_dp(EXAMPLE), 525152);
+
+        let mut counter = SpringCounter::default();
+        let counts = parse(EXAMPLE).iter().map(|row| counter.count(&row.unfold(5).springs, &row.unfold(5).rules)).collect_vec();
+        assert_eq!(counts, vec![1, 16384, 1, 16, 2500, 506250]);
+    }
+
+    // rows of up to `max_len` springs with rules that roughly fit them
+    fn generate_rows(num_rows: usize, max_len: usize, seed: u64) -> Vec<SpringRow> {
+        let mut rng = TestRng::new(seed);
+        (0..num_rows).map(|_| {
+            let springs = (0..1 + rng.below(max_len)).map(|_| {
+                rng.pick(&[Spring::Operational, Spring::Damaged, Spring::Unknown, Spring::Unknown])
+            }).collect_vec();
+            let rules = (0..1 + rng.below(springs.len() / 3 + 1)).map(|_| 1 + rng.below(3)).collect_vec();
+            (springs, rules).into()
+        }).collect()
+    }
+
+    #[test]
+    fn dp_matches_recursion() {
+        let mut counter = SpringCounter::default();
+        for row in generate_rows(300, 14, 0x2023_1212) {
+            for factor in 1..=3 {
+                let unfolded = row.unfold(factor);
+                let expected = num_possibilities(&unfolded) as u128;
+                assert_eq!(counter.count(&unfolded.springs, &unfolded.rules), expected, "{unfolded:?}");
+                assert_eq!(counter.count_unfolded(&UnfoldedRow { springs: &row.springs, rules: &row.rules, factor }), expected, "{unfolded:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn dp_counts_past_u64() {
+        // k single damaged springs among n unknowns is choosing where the k runs start out of n - k + 1 places
+        let (len, runs) = (140, 40);
+        let row = SpringRow::from((vec![Spring::Unknown; len], vec![1; runs]));
+        let places = (len - runs + 1) as u128;
+        let expected = (1..=runs as u128).fold(1, |choose, i| choose * (places - runs as u128 + i) / i);
+        assert!(expected > u64::MAX as u128);
+        assert_eq!(SpringCounter::default().count(&row.springs, &row.rules), expected);
+    }
+
+    #[test]
+    #[ignore = "benchmark, run with --ignored --nocapture"]
+    fn dp_benchmark() {
+        let rows = generate_rows(1_000, 20, 0x2023_1212);
+
+        let start = std::time::Instant::now();
+        let recursion: u128 = rows.iter().map(|row| num_possibilities(&row.unfold(5)) as u128).sum();
+        println!("recursion: {recursion} in {:?}", start.elapsed());
+
+        let start = std::time::Instant::now();
+        let dp = count_unfolded(&rows, 5);
+        println!("dp:        {dp} in {:?}", start.elapsed());
+
+        assert_eq!(recursion, dp);
+    }
+
+    // every assignment of the unknown springs whose damaged runs come out as the rules
+    fn brute_force(row: &SpringRow) -> Vec<String> {
+        let unknowns = row.springs.iter().positions(|&spring| spring == Spring::Unknown).collect_vec();
+        (0..1u32 << unknowns.len()).map(|mask| {
+            let mut springs = row.springs.clone();
+            for (bit, &ix) in unknowns.iter().enumerate() {
+                springs[ix] = if mask >> bit & 1 == 1 { Spring::Damaged } else { Spring::Operational };
+            }
+            springs
+        }).filter(|springs| {
+            springs.iter().dedup_with_count().filter(|&(_, &spring)| spring == Spring::Damaged).map(|(len, _)| len).collect_vec() == row.rules
+        }).map(|springs| render(&springs)).sorted().collect()
+    }
+
+    #[test]
+    fn arrangements_example() {
+        let arrangements = Arrangements::new(&parse("?###???????? 3,2,1")[0]);
+        assert_eq!(arrangements.total, 10);
+        assert_eq!(arrangements.iter().map(|springs| render(&springs)).collect_vec(), vec![
+            ".###....##.#",
+            ".###...##..#",
+            ".###...##.#.",
+            ".###..##...#",
+            ".###..##..#.",
+            ".###..##.#..",
+            ".###.##....#",
+            ".###.##...#.",
+            ".###.##..#..",
+            ".###.##.#...",
+        ]);
+        assert_eq!(arrangements.nth(10), None);
+        assert_eq!(Arrangements::new(&parse("#.# 1")[0]).iter().count(), 0);
+    }
+
+    #[test]
+    fn arrangements_match_brute_force() {
+        for row in generate_rows(300, 14, 0x2023_1212) {
+            let arrangements = Arrangements::new(&row);
+            // '#' sorts before '.' as characters, so the brute force list comes out reversed
+            let listed = arrangements.iter().map(|springs| render(&springs)).rev().collect_vec();
+            assert_eq!(listed, brute_force(&row), "{row:?}");
+        }
+    }
+
+    #[test]
+    fn sample_is_uniform() {
+        let arrangements = Arrangements::new(&parse("?###???????? 3,2,1")[0]);
+        let mut rng = TestRng::new(0x2023_1212);
+        let mut next_random = || rng.next_u64();
+        let counts = (0..10_000).map(|_| render(&arrangements.sample(&mut next_random).unwrap())).counts();
+        assert_eq!(counts.len(), 10);
+        assert!(counts.values().all(|&count| (850..1150).contains(&count)), "{counts:?}");
+        assert_eq!(Arrangements::new(&parse("#.# 1")[0]).sample(next_random), None);
+    }
+
+    #[test]
+    fn arrangements_report_example() {
+        let mut rng = TestRng::new(0x2023_1212);
+        let report = arrangements_report(&parse("???.### 1,1,3\n?###???????? 3,2,1\n#.# 1"), || rng.next_u64());
+        let lines = report.lines().skip(1).collect_vec();
+        assert_eq!(lines[0], "line 1: 1 arrangements, first #.#.###, last #.#.###, random #.#.###");
+        assert!(lines[1].starts_with("line 2: 10 arrangements, first .###....##.#, last .###.##.#..., random "));
+        assert_eq!(lines[2], "line 3: no arrangements");
+    }
+
+    #[test]
+    fn part1_arrangements_reproducible() {
+        let input = "???.### 1,1,3\n.??..??...?##. 1,1,3\n?###???????? 3,2,1";
+        assert_eq!(part1_arrangements(input), part1_arrangements(input));
+    }
+
+    #[test]
+    fn part1_nonogram_example() {
+        assert_eq!(part1_nonogram("1,1\n0\n3\n\n1,1\n1\n1,1"), "\n#.#\n...\n###");
+        assert_eq!(part1_nonogram("1\n1\n\n1\n1"), "\nmore than one solution, including\n#.\n.#\n\nand\n.#\n#.");
+        assert_eq!(part1_nonogram("2\n0\n\n1\n0"), "no solution");
+        assert_eq!(part1_nonogram("???.### 1,1,3\n.??..??...?##. 1,1,3"), "input is not a nonogram");
+    }
+
 }
\ No newline at end of file
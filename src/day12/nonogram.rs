@@ -0,0 +1,269 @@
+use std::{collections::VecDeque, fmt::Display};
+
+use itertools::Itertools;
+use nom::{IResult, combinator::{all_consuming, map}, character::complete::line_ending, multi::separated_list1, sequence::{separated_pair, pair}};
+
+use super::{Spring, SpringCounter, parse_rules};
+
+
+// a nonogram is a grid of spring rows where the columns have rules too. filled cells are damaged
+// springs, blank cells are operational ones
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct Nonogram {
+    row_clues: Vec<Vec<usize>>,
+    col_clues: Vec<Vec<usize>>,
+}
+
+// a line with nothing filled in is written as a single 0
+fn parse_clue(input: &str) -> IResult<&str, Vec<usize>> {
+    map(parse_rules, |rules| rules.into_iter().filter(|&rule| rule != 0).collect())(input)
+}
+
+fn parse_clues(input: &str) -> IResult<&str, Vec<Vec<usize>>> {
+    separated_list1(line_ending, parse_clue)(input)
+}
+
+// row clues, a blank line, then column clues. None if the input isn't laid out like that
+pub(super) fn parse(input: &str) -> Option<Nonogram> {
+    let (_, (row_clues, col_clues)) = all_consuming(
+        separated_pair(parse_clues, pair(line_ending, line_ending), parse_clues)
+    )(input).ok()?;
+    Some(Nonogram { row_clues, col_clues })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum NonogramSolution {
+    Unsolvable,
+    Unique(Vec<Vec<Spring>>),
+    // the first two solutions found, there may be more
+    Ambiguous(Vec<Vec<Spring>>, Vec<Vec<Spring>>),
+}
+
+// narrows a line down to the cells every arrangement of it agrees on. a cell that is damaged in
+// none of the arrangements has to be operational and one damaged in all of them has to be damaged,
+// so one count per unknown cell is enough. returns None if nothing fits the line at all
+fn solve_line(counter: &mut SpringCounter, line: &mut [Spring], rules: &[usize]) -> Option<Vec<usize>> {
+    let total = counter.count(line, rules);
+    if total == 0 {
+        return None;
+    }
+
+    let mut changed = vec![];
+    for ix in 0..line.len() {
+        if line[ix] != Spring::Unknown {
+            continue;
+        }
+        line[ix] = Spring::Damaged;
+        let damaged = counter.count(line, rules);
+        line[ix] = if damaged == 0 {
+            Spring::Operational
+        } else if damaged == total {
+            Spring::Damaged
+        } else {
+            Spring::Unknown
+        };
+        if line[ix] != Spring::Unknown {
+            changed.push(ix);
+        }
+    }
+    Some(changed)
+}
+
+impl Nonogram {
+    fn width(&self) -> usize {
+        self.col_clues.len()
+    }
+
+    fn height(&self) -> usize {
+        self.row_clues.len()
+    }
+
+    // solves lines until none of them can be narrowed any further, only revisiting the lines that
+    // cross a cell that just got decided. returns false if some line can't be satisfied
+    fn propagate(&self, counter: &mut SpringCounter, grid: &mut [Vec<Spring>]) -> bool {
+        // rows are 0..height, columns come after them
+        let mut queued = vec![true; self.height() + self.width()];
+        let mut to_solve = VecDeque::from_iter(0..self.height() + self.width());
+        while let Some(line_ix) = to_solve.pop_front() {
+            queued[line_ix] = false;
+            let crossing = if line_ix < self.height() {
+                let row = line_ix;
+                let Some(changed) = solve_line(counter, &mut grid[row], &self.row_clues[row]) else {
+                    return false;
+                };
+                changed.into_iter().map(|col| self.height() + col).collect_vec()
+            } else {
+                let col = line_ix - self.height();
+                let mut line = grid.iter().map(|row| row[col]).collect_vec();
+                let Some(changed) = solve_line(counter, &mut line, &self.col_clues[col]) else {
+                    return false;
+                };
+                for (row, &spring) in line.iter().enumerate() {
+                    grid[row][col] = spring;
+                }
+                changed
+            };
+            for line_ix in crossing {
+                if !queued[line_ix] {
+                    queued[line_ix] = true;
+                    to_solve.push_back(line_ix);
+                }
+            }
+        }
+        true
+    }
+
+    // propagates, then guesses the first undecided cell both ways, stopping once two solutions are found
+    fn search(&self, counter: &mut SpringCounter, mut grid: Vec<Vec<Spring>>, solutions: &mut Vec<Vec<Vec<Spring>>>) {
+        if solutions.len() >= 2 || !self.propagate(counter, &mut grid) {
+            return;
+        }
+        let undecided = grid.iter().enumerate().find_map(|(row, springs)| {
+            springs.iter().position(|&spring| spring == Spring::Unknown).map(|col| (row, col))
+        });
+        match undecided {
+            None => solutions.push(grid),
+            Some((row, col)) => {
+                for guess in [Spring::Damaged, Spring::Operational] {
+                    let mut guessed = grid.clone();
+                    guessed[row][col] = guess;
+                    self.search(counter, guessed, solutions);
+                }
+            }
+        }
+    }
+
+    pub(super) fn solve(&self) -> NonogramSolution {
+        let mut solutions = vec![];
+        self.search(&mut SpringCounter::default(), vec![vec![Spring::Unknown; self.width()]; self.height()], &mut solutions);
+
+        let mut solutions = solutions.into_iter();
+        match (solutions.next(), solutions.next()) {
+            (None, _) => NonogramSolution::Unsolvable,
+            (Some(solution), None) => NonogramSolution::Unique(solution),
+            (Some(first), Some(second)) => NonogramSolution::Ambiguous(first, second),
+        }
+    }
+}
+
+fn render(grid: &[Vec<Spring>]) -> String {
+    grid.iter().map(|row| row.iter().map(|&spring| char::from(spring)).collect::<String>()).join("\n")
+}
+
+impl Display for NonogramSolution {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NonogramSolution::Unsolvable => write!(f, "no solution"),
+            NonogramSolution::Unique(grid) => write!(f, "\n{}", render(grid)),
+            NonogramSolution::Ambiguous(first, second) => {
+                write!(f, "\nmore than one solution, including\n{}\n\nand\n{}", render(first), render(second))
+            }
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_rng::TestRng;
+
+    fn clues_of(lines: impl Iterator<Item = Vec<Spring>>) -> Vec<Vec<usize>> {
+        lines.map(|line| {
+            line.into_iter().dedup_with_count().filter(|&(_, spring)| spring == Spring::Damaged).map(|(len, _)| len).collect()
+        }).collect()
+    }
+
+    fn puzzle_for(grid: &[Vec<Spring>]) -> Nonogram {
+        Nonogram {
+            row_clues: clues_of(grid.iter().cloned()),
+            col_clues: clues_of((0..grid[0].len()).map(|col| grid.iter().map(|row| row[col]).collect())),
+        }
+    }
+
+    #[test]
+    fn parser_test() {
+        assert_eq!(parse("1,1
+0
+3
+
+2
+1
+2"), Some(Nonogram {
+            row_clues: vec![vec![1, 1], vec![], vec![3]],
+            col_clues: vec![vec![2], vec![1], vec![2]],
+        }));
+        assert_eq!(parse("???.### 1,1,3"), None);
+    }
+
+    #[test]
+    fn solve_line_test() {
+        let mut counter = SpringCounter::default();
+        let mut line = vec![Spring::Unknown; 10];
+        // an 8 has to cover the middle 6 wherever it goes
+        assert_eq!(solve_line(&mut counter, &mut line, &[8]), Some(vec![2, 3, 4, 5, 6, 7]));
+        assert_eq!(render(&[line]), "??######??");
+
+        let mut line = "?#??.?".chars().map(|chr| Spring::try_from(chr).unwrap()).collect_vec();
+        assert_eq!(solve_line(&mut counter, &mut line, &[3]), Some(vec![2, 5]));
+        assert_eq!(render(&[line.clone()]), "?##?..");
+        assert_eq!(solve_line(&mut counter, &mut line, &[5]), None);
+    }
+
+    #[test]
+    fn solve_example() {
+        let puzzle = parse("0
+1,1
+0
+1,1
+3
+
+1
+1,1
+1
+1,1
+1").unwrap();
+        let NonogramSolution::Unique(solution) = puzzle.solve() else {
+            panic!("expected a unique solution");
+        };
+        assert_eq!(render(&solution), ".....
+.#.#.
+.....
+#...#
+.###.");
+    }
+
+    #[test]
+    fn solve_ambiguous_and_unsolvable() {
+        // either diagonal of a 2x2 square fits
+        let NonogramSolution::Ambiguous(first, second) = parse("1\n1\n\n1\n1").unwrap().solve() else {
+            panic!("expected two solutions");
+        };
+        assert_eq!(render(&first), "#.\n.#");
+        assert_eq!(render(&second), ".#\n#.");
+
+        assert_eq!(parse("2\n0\n\n1\n0").unwrap().solve(), NonogramSolution::Unsolvable);
+        // every line fits on its own, but the rows fill three cells and the columns only two
+        assert_eq!(parse("1\n2\n\n1\n1").unwrap().solve(), NonogramSolution::Unsolvable);
+    }
+
+    #[test]
+    fn solve_generated() {
+        let mut rng = TestRng::new(0x2023_1212);
+        for _ in 0..30 {
+            let (height, width) = (1 + rng.below(10), 1 + rng.below(10));
+            let grid = rng.grid(height, width, |rng| rng.pick(&[Spring::Damaged, Spring::Operational]));
+            let puzzle = puzzle_for(&grid);
+            match puzzle.solve() {
+                NonogramSolution::Unique(solution) => assert_eq!(solution, grid),
+                NonogramSolution::Ambiguous(first, second) => {
+                    assert_ne!(first, second);
+                    assert_eq!(puzzle_for(&first), puzzle);
+                    assert_eq!(puzzle_for(&second), puzzle);
+                }
+                NonogramSolution::Unsolvable => panic!("{}", render(&grid)),
+            }
+        }
+    }
+
+}
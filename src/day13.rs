@@ -1,4 +1,6 @@
 
+use std::fmt::Display;
+
 use itertools::Itertools;
 use aoc_runner_derive::aoc;
 use nom::{IResult, combinator::{map_res, all_consuming, map}, character::complete::{line_ending, one_of}, multi::{many1, separated_list1, many1_count}};
@@ -137,9 +139,200 @@ fn part2(input: &str) -> u64 {
 }
 
 
+// every row and column of a pattern as a bitmask of where the rocks are, so the number of cells two
+// lines differ in is a single xor and popcount
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct BitPattern {
+    rows: Vec<u64>,
+    cols: Vec<u64>
+}
+impl From<&Pattern> for BitPattern {
+    fn from(pattern: &Pattern) -> Self {
+        let (height, width) = (pattern.cells.len(), pattern.cells[0].len());
+        assert!(height <= 64 && width <= 64, "pattern of {height}x{width} does not fit in bitmasks");
+        let mut rows = vec![0; height];
+        let mut cols = vec![0; width];
+        for (row, line) in pattern.cells.iter().enumerate() {
+            for (col, &ground) in line.iter().enumerate() {
+                if ground == Ground::Rock {
+                    rows[row] |= 1 << col;
+                    cols[col] |= 1 << row;
+                }
+            }
+        }
+        Self { rows, cols }
+    }
+}
+
+// where a mirror sits, as the number of rows above it or columns left of it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MirrorLine {
+    Row(usize),
+    Column(usize)
+}
+impl MirrorLine {
+    fn summary(&self) -> u64 {
+        match *self {
+            MirrorLine::Row(rows) => rows as u64 * 100,
+            MirrorLine::Column(cols) => cols as u64
+        }
+    }
+}
+
+// positions between lines where folding the lines over each other leaves exactly `smudges` cells
+// that don't match up
+fn mirror_positions(lines: &[u64], smudges: u32) -> impl Iterator<Item = usize> + '_ {
+    (1..lines.len()).filter(move |&split| {
+        let (before, after) = lines.split_at(split);
+        let mut differences = 0;
+        for (a, b) in before.iter().rev().zip(after) {
+            differences += (a ^ b).count_ones();
+            if differences > smudges {
+                return false;
+            }
+        }
+        differences == smudges
+    })
+}
+
+// every mirror in the pattern that needs exactly `smudges` cells fixed, rows first
+fn detect_mirror_with_smudges(pattern: &Pattern, smudges: u32) -> Vec<MirrorLine> {
+    let bits = BitPattern::from(pattern);
+    mirror_positions(&bits.rows, smudges).map(MirrorLine::Row)
+        .chain(mirror_positions(&bits.cols, smudges).map(MirrorLine::Column))
+        .collect()
+}
+
+fn summarize_with_smudges(input: &str, smudges: u32) -> u64 {
+    let input = parse(input);
+
+    input.into_iter().map(|pattern| {
+        match detect_mirror_with_smudges(&pattern, smudges).first() {
+            Some(line) => line.summary(),
+            None => panic!("Did not detect any mirror from pattern {pattern:?}")
+        }
+    }).sum()
+}
+
+#[aoc(day13, part1, bitmask)]
+fn part1_bitmask(input: &str) -> u64 {
+    summarize_with_smudges(input, 0)
+}
+
+#[aoc(day13, part2, bitmask)]
+fn part2_bitmask(input: &str) -> u64 {
+    summarize_with_smudges(input, 1)
+}
+
+impl From<Ground> for char {
+    fn from(ground: Ground) -> Self {
+        match ground {
+            Ground::Ash => '.',
+            Ground::Rock => '#'
+        }
+    }
+}
+
+// the one cell that keeps a mirror from being perfect. `smudge` is on the top or left side of the
+// mirror and `reflection` is where it lands on the other side; cleaning either one fixes it
+#[derive(Debug, Clone)]
+struct SmudgeReport {
+    pattern: Pattern,
+    mirror: MirrorLine,
+    smudge: (usize, usize),
+    reflection: (usize, usize)
+}
+impl SmudgeReport {
+    fn find(pattern: &Pattern) -> Option<Self> {
+        let mirror = *detect_mirror_with_smudges(pattern, 1).first()?;
+        let bits = BitPattern::from(pattern);
+        let (lines, split) = match mirror {
+            MirrorLine::Row(split) => (&bits.rows, split),
+            MirrorLine::Column(split) => (&bits.cols, split)
+        };
+        let (offset, diff) = (0..split.min(lines.len() - split))
+            .map(|offset| (offset, lines[split - 1 - offset] ^ lines[split + offset]))
+            .find(|&(_, diff)| diff != 0)?;
+        let (line, mirrored, across) = (split - 1 - offset, split + offset, diff.trailing_zeros() as usize);
+        let (smudge, reflection) = match mirror {
+            MirrorLine::Row(_) => ((line, across), (mirrored, across)),
+            MirrorLine::Column(_) => ((across, line), (across, mirrored))
+        };
+        Some(Self { pattern: pattern.clone(), mirror, smudge, reflection })
+    }
+}
+// the pattern with the smudge drawn as * and the mirror drawn in between the lines it separates.
+// coordinates are counted from one like the puzzle text does
+impl Display for SmudgeReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (row, col) = self.smudge;
+        let (mirrored_row, mirrored_col) = self.reflection;
+        let cleaned = match self.pattern.cells[row][col] {
+            Ground::Ash => Ground::Rock,
+            Ground::Rock => Ground::Ash
+        };
+        match self.mirror {
+            MirrorLine::Row(rows) => writeln!(f, "mirror between rows {} and {}", rows, rows + 1)?,
+            MirrorLine::Column(cols) => writeln!(f, "mirror between columns {} and {}", cols, cols + 1)?
+        }
+        writeln!(f, "smudge at row {}, column {} should be {} to match row {}, column {}",
+            row + 1, col + 1, char::from(cleaned), mirrored_row + 1, mirrored_col + 1)?;
+
+        for (row_ix, line) in self.pattern.cells.iter().enumerate() {
+            if self.mirror == MirrorLine::Row(row_ix) {
+                writeln!(f, "{}", "-".repeat(line.len()))?;
+            }
+            for (col_ix, &ground) in line.iter().enumerate() {
+                if self.mirror == MirrorLine::Column(col_ix) {
+                    write!(f, "|")?;
+                }
+                write!(f, "{}", if (row_ix, col_ix) == self.smudge { '*' } else { char::from(ground) })?;
+            }
+            if row_ix + 1 < self.pattern.cells.len() {
+                writeln!(f)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct CleanupReport {
+    patterns: Vec<SmudgeReport>,
+    total: u64
+}
+impl From<Vec<Pattern>> for CleanupReport {
+    fn from(patterns: Vec<Pattern>) -> Self {
+        let patterns = patterns.iter().map(|pattern| match SmudgeReport::find(pattern) {
+            Some(report) => report,
+            None => panic!("Did not detect any mirror from pattern {pattern:?}")
+        }).collect_vec();
+        Self {
+            total: patterns.iter().map(|report| report.mirror.summary()).sum(),
+            patterns
+        }
+    }
+}
+impl Display for CleanupReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f)?;
+        for (ix, report) in self.patterns.iter().enumerate() {
+            writeln!(f, "pattern {}: {report}", ix + 1)?;
+            writeln!(f)?;
+        }
+        write!(f, "total: {}", self.total)
+    }
+}
+
+#[aoc(day13, part2, report)]
+fn part2_report(input: &str) -> CleanupReport {
+    CleanupReport::from(parse(input))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_rng::TestRng;
 
     #[test]
     fn part1_example() {
@@ -179,4 +372,102 @@ mod tests {
 #....#..#"), 400);
     }
 
-}
\ No newline at end of file
+    const EXAMPLE: &str = "#.##..##.
+..#.##.#.
+##......#
+##......#
+..#.##.#.
+..##..##.
+#.#.##.#.
+
+#...##..#
+#....#..#
+..##..###
+#####.##.
+#####.##.
+..##..###
+#....#..#";
+
+    #[test]
+    fn bitmask_example() {
+        assert_eq!(part1_bitmask(EXAMPLE), 405);
+        assert_eq!(part2_bitmask(EXAMPLE), 400);
+
+        let patterns = parse(EXAMPLE);
+        assert_eq!(BitPattern::from(&patterns[0]).rows[0], 0b011001101);
+        assert_eq!(detect_mirror_with_smudges(&patterns[0], 0), vec![MirrorLine::Column(5)]);
+        assert_eq!(detect_mirror_with_smudges(&patterns[0], 1), vec![MirrorLine::Row(3)]);
+        assert_eq!(detect_mirror_with_smudges(&patterns[1], 1), vec![MirrorLine::Row(1)]);
+    }
+
+    #[test]
+    fn detect_all_mirrors() {
+        let pattern = &parse("##.
+##.
+##.")[0];
+        assert_eq!(detect_mirror_with_smudges(pattern, 0), vec![MirrorLine::Row(1), MirrorLine::Row(2), MirrorLine::Column(1)]);
+        assert_eq!(detect_mirror_with_smudges(pattern, 1), vec![]);
+        assert_eq!(detect_mirror_with_smudges(pattern, 3), vec![MirrorLine::Column(2)]);
+    }
+
+    #[test]
+    fn bitmask_matches_cell_by_cell() {
+        let mut rng = TestRng::new(0x2023_1213);
+        for _ in 0..200 {
+            let (height, width) = (1 + rng.below(12), 1 + rng.below(12));
+            // mostly ash so that mirrors with few smudges turn up
+            let cells = rng.grid(height, width, |rng| {
+                if rng.below(5) == 0 { Ground::Rock } else { Ground::Ash }
+            });
+            let pattern = Pattern { cells };
+
+            let cell = |row: usize, col: usize| pattern.cells[row][col];
+            for smudges in 0..4 {
+                let rows = (1..height).filter(|&split| {
+                    (0..split.min(height - split)).flat_map(|offset| (0..width).map(move |col| (offset, col)))
+                        .filter(|&(offset, col)| cell(split - 1 - offset, col) != cell(split + offset, col)).count() == smudges as usize
+                }).map(MirrorLine::Row);
+                let cols = (1..width).filter(|&split| {
+                    (0..split.min(width - split)).flat_map(|offset| (0..height).map(move |row| (offset, row)))
+                        .filter(|&(offset, row)| cell(row, split - 1 - offset) != cell(row, split + offset)).count() == smudges as usize
+                }).map(MirrorLine::Column);
+                assert_eq!(detect_mirror_with_smudges(&pattern, smudges), rows.chain(cols).collect_vec(), "{pattern:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn smudge_report_example() {
+        let report = part2_report(EXAMPLE);
+        assert_eq!(report.total, 400);
+        assert_eq!(report.patterns.iter().map(|report| (report.mirror, report.smudge, report.reflection)).collect_vec(), vec![
+            (MirrorLine::Row(3), (0, 0), (5, 0)),
+            (MirrorLine::Row(1), (0, 4), (1, 4))
+        ]);
+        assert_eq!(report.patterns[0].to_string(), "mirror between rows 3 and 4
+smudge at row 1, column 1 should be . to match row 6, column 1
+*.##..##.
+..#.##.#.
+##......#
+---------
+##......#
+..#.##.#.
+..##..##.
+#.#.##.#.");
+    }
+
+    #[test]
+    fn smudge_report_columns() {
+        let report = SmudgeReport::find(&parse("#.##.
+.#..#
+##.##")[0]).unwrap();
+        assert_eq!((report.mirror, report.smudge, report.reflection), (MirrorLine::Column(3), (2, 2), (2, 3)));
+        assert_eq!(report.to_string(), "mirror between columns 3 and 4
+smudge at row 3, column 3 should be # to match row 3, column 4
+#.#|#.
+.#.|.#
+##*|##");
+        assert!(SmudgeReport::find(&parse("#.\n.#")[0]).is_none());
+    }
+
+}
@@ -208,9 +208,147 @@ fn part2(input: &str) -> u64 {
 }
 
 
+impl From<Rock> for u8 {
+    fn from(rock: Rock) -> Self {
+        match rock {
+            Rock::Rounded => b'O',
+            Rock::Square => b'#',
+            Rock::Empty => b'.'
+        }
+    }
+}
+
+// a fixed pseudo-random key per cell (splitmix64). a state's hash is the wrapping sum of the keys
+// of every cell holding a rounded rock, which moving one rock updates in constant time
+fn cell_key(pos: usize) -> u64 {
+    let mut key = (pos as u64).wrapping_add(0x9E37_79B9_7F4A_7C15);
+    key = (key ^ (key >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    key = (key ^ (key >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    key ^ (key >> 31)
+}
+
+// one byte per cell, row-major, with the hash of where the rounded rocks are kept up to date
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Platform {
+    cells: Vec<u8>,
+    rows: usize,
+    cols: usize,
+    hash: u64,
+}
+impl Display for Platform {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for row in self.cells.chunks(self.cols) {
+            writeln!(f, "{}", String::from_utf8_lossy(row))?;
+        }
+        Ok(())
+    }
+}
+impl Platform {
+    fn from_vec(input: Vec<Vec<Rock>>) -> Self {
+        let rows = input.len();
+        let cols = input[0].len();
+        let cells = input.into_iter().flatten().map(u8::from).collect_vec();
+        let hash = cells.iter().positions(|&cell| cell == b'O').fold(0u64, |hash, pos| hash.wrapping_add(cell_key(pos)));
+        Self { cells, rows, cols, hash }
+    }
+
+    // rolls every rounded rock in the line starting at `start` towards that end. the line is split into
+    // segments by the square rocks, and each rounded rock just drops into the next free cell of its segment
+    fn tilt_line(&mut self, start: usize, stride: isize, len: usize) {
+        let pos = |ix: usize| start.wrapping_add_signed(ix as isize * stride);
+        let mut free = 0;
+        for ix in 0..len {
+            match self.cells[pos(ix)] {
+                b'#' => free = ix + 1,
+                b'O' => {
+                    if ix != free {
+                        let (from, to) = (pos(ix), pos(free));
+                        self.cells.swap(from, to);
+                        self.hash = self.hash.wrapping_sub(cell_key(from)).wrapping_add(cell_key(to));
+                    }
+                    free += 1;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn tilt(&mut self, dir: Direction) {
+        let (rows, cols) = (self.rows, self.cols);
+        match dir {
+            Direction::North => (0..cols).for_each(|col| self.tilt_line(col, cols as isize, rows)),
+            Direction::South => (0..cols).for_each(|col| self.tilt_line((rows - 1) * cols + col, -(cols as isize), rows)),
+            Direction::West => (0..rows).for_each(|row| self.tilt_line(row * cols, 1, cols)),
+            Direction::East => (0..rows).for_each(|row| self.tilt_line(row * cols + cols - 1, -1, cols)),
+        }
+    }
+
+    fn spin_cycle(&mut self) {
+        Direction::spin_cycle().iter().for_each(|&dir| self.tilt(dir));
+    }
+
+    fn north_load(&self) -> u64 {
+        self.cells.iter().positions(|&cell| cell == b'O').map(|pos| (self.rows - pos / self.cols) as u64).sum()
+    }
+}
+
+// the platform's north load after each spin cycle up to the first repeated state. after `prefix`
+// cycles the platform goes round the same `cycle_len` states forever
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SpinCycles {
+    prefix: usize,
+    cycle_len: usize,
+    loads: Vec<u64>,
+}
+impl SpinCycles {
+    fn detect(platform: Platform) -> Self {
+        Self::detect_by(platform, |platform| platform.hash)
+    }
+
+    // states are looked up by `key`, but two states only count as the same once their cells match,
+    // since different platforms can share a hash
+    fn detect_by(mut platform: Platform, key: impl Fn(&Platform) -> u64) -> Self {
+        let mut seen: HashMap<u64, Vec<usize>> = HashMap::new();
+        let mut states = vec![];
+        let mut loads = vec![];
+        loop {
+            let same_key = seen.entry(key(&platform)).or_default();
+            if let Some(&prefix) = same_key.iter().find(|&&step| states[step] == platform.cells) {
+                return Self { prefix, cycle_len: loads.len() - prefix, loads };
+            }
+            same_key.push(loads.len());
+            states.push(platform.cells.clone());
+            loads.push(platform.north_load());
+            platform.spin_cycle();
+        }
+    }
+
+    fn load_after(&self, cycles: u64) -> u64 {
+        let prefix = self.prefix as u64;
+        let step = if cycles < prefix { cycles } else { prefix + (cycles - prefix) % self.cycle_len as u64 };
+        self.loads[step as usize]
+    }
+}
+
+#[aoc(day14, part1, dense)]
+fn part1_dense(input: &str) -> u64 {
+    let mut platform = Platform::from_vec(parse(input));
+    platform.tilt(Direction::North);
+
+    platform.north_load()
+}
+
+#[aoc(day14, part2, dense)]
+fn part2_dense(input: &str) -> u64 {
+    let platform = Platform::from_vec(parse(input));
+
+    SpinCycles::detect(platform).load_after(1_000_000_000)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_rng::TestRng;
 
     #[test]
     fn part1_example() {
@@ -240,4 +378,51 @@ O.#..O.#.#
 #OO..#...."), 64);
     }
 
-}
\ No newline at end of file
+    const EXAMPLE: &str = "O....#....
+O.OO#....#
+.....##...
+OO.#O....O
+.O.....O#.
+O.#..O.#.#
+..O..#O..O
+.......O..
+#....###..
+#OO..#....";
+
+    #[test]
+    fn dense_example() {
+        assert_eq!(part1_dense(EXAMPLE), 136);
+        assert_eq!(part2_dense(EXAMPLE), 64);
+
+        let cycles = SpinCycles::detect(Platform::from_vec(parse(EXAMPLE)));
+        assert_eq!((cycles.prefix, cycles.cycle_len), (3, 7));
+        // before anything has been tilted
+        assert_eq!(cycles.load_after(0), 104);
+        assert_eq!(cycles.load_after(1), 87);
+        assert_eq!(cycles.load_after(10), cycles.load_after(3));
+        assert_eq!(cycles.load_after(1_000_000_000), 64);
+    }
+
+    #[test]
+    fn dense_matches_grid() {
+        let mut rng = TestRng::new(0x2023_1214);
+        for _ in 0..20 {
+            let (rows, cols) = (1 + rng.below(15), 1 + rng.below(15));
+            let input = rng.grid(rows, cols, |rng| rng.pick(&['O', '#', '.', '.'])).iter().map(|row| row.iter().collect::<String>()).join("\n");
+
+            let mut grid = Grid::from_vec(parse(&input));
+            let mut platform = Platform::from_vec(parse(&input));
+            let cycles = SpinCycles::detect(platform.clone());
+            // with every state hashing the same, only comparing the cells tells them apart
+            assert_eq!(SpinCycles::detect_by(platform.clone(), |_| 0), cycles);
+            for cycle in 0..30 {
+                assert_eq!(platform.to_string(), grid.to_string());
+                assert_eq!(platform.hash, Platform::from_vec(parse(platform.to_string().trim_end())).hash);
+                assert_eq!(cycles.load_after(cycle), platform.north_load());
+                grid.spin_cycle();
+                platform.spin_cycle();
+            }
+        }
+    }
+
+}
@@ -70,9 +70,72 @@ fn part2(input: &str) -> u32 {
 }
 
 
+// every number on a card is below 100, so a single u128 can hold each list
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+struct BitsetScratchcard {
+    id: u32,
+    winners: u128,
+    num_list: u128
+}
+impl BitsetScratchcard {
+    fn num_matches(&self) -> u32 {
+        (self.winners & self.num_list).count_ones()
+    }
+}
+impl From<Scratchcard> for BitsetScratchcard {
+    fn from(card: Scratchcard) -> Self {
+        Self {
+            id: card.id,
+            winners: to_bitset(&card.winners),
+            num_list: to_bitset(&card.num_list)
+        }
+    }
+}
+
+fn to_bitset(nums: &[u32]) -> u128 {
+    nums.iter().fold(0, |set, &num| {
+        assert!(num < u128::BITS, "card number {num} does not fit in a bitset");
+        set | (1 << num)
+    })
+}
+
+fn parse_bitset(input: &str) -> Vec<BitsetScratchcard> {
+    parse(input).into_iter().map(BitsetScratchcard::from).collect()
+}
+
+#[aoc(day4, part1, bitset)]
+fn part1_bitset(input: &str) -> u32 {
+    let input = parse_bitset(input);
+
+    input.into_iter().map(|card| match card.num_matches() {
+        0 => 0,
+        exp => 2u32.pow(exp - 1)
+    }).sum()
+}
+
+#[aoc(day4, part2, bitset)]
+fn part2_bitset(input: &str) -> u32 {
+    let input = parse_bitset(input);
+
+    let mut card_counts = vec![1u32; input.len()];
+    input.into_iter().for_each(|card| {
+        let id = (card.id - 1) as usize;
+        let copies = card_counts[id];
+        let num_winners = card.num_matches() as usize;
+        for count in &mut card_counts[id + 1..=id + num_winners] {
+            *count += copies;
+        }
+    });
+
+    card_counts.into_iter().sum()
+}
+
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_rng::TestRng;
+    use itertools::Itertools;
 
     #[test]
     fn parser_test() {
@@ -94,6 +157,11 @@ Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1"), vec![Scratchcard {
             winners: vec![1, 21, 53, 59, 44],
             num_list: vec![69, 82, 63, 72, 16, 21, 14, 1]
         }]);
+        assert_eq!(parse_bitset("Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53"), vec![BitsetScratchcard {
+            id: 1,
+            winners: (1 << 41) | (1 << 48) | (1 << 83) | (1 << 86) | (1 << 17),
+            num_list: (1 << 83) | (1 << 86) | (1 << 6) | (1 << 31) | (1 << 17) | (1 << 9) | (1 << 48) | (1 << 53)
+        }]);
     }
 
     #[test]
@@ -116,4 +184,72 @@ Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
 Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11"), 30);
     }
 
+    #[test]
+    fn part1_bitset_example() {
+        assert_eq!(part1_bitset("Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
+Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
+Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
+Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
+Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11"), 13);
+    }
+
+    #[test]
+    fn part2_bitset_example() {
+        assert_eq!(part2_bitset("Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
+Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
+Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
+Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
+Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11"), 30);
+    }
+
+    // builds cards shaped like the real input (10 winners, 25 numbers, each list distinct and below 100)
+    fn generate_input(num_cards: u32, seed: u64) -> String {
+        let mut rng = TestRng::new(seed);
+        let mut distinct_nums = |len| {
+            let mut nums = Vec::with_capacity(len);
+            while nums.len() < len {
+                let num = rng.below(99) as u32 + 1;
+                if !nums.contains(&num) {
+                    nums.push(num);
+                }
+            }
+            nums.into_iter().map(|num| format!("{num:>2}")).join(" ")
+        };
+        (1..=num_cards).map(|id| {
+            format!("Card {id:>7}: {} | {}", distinct_nums(10), distinct_nums(25))
+        }).join("\n")
+    }
+
+    fn naive_matches(card: &Scratchcard) -> usize {
+        card.num_list.iter().filter(|num| card.winners.contains(num)).count()
+    }
+
+    #[test]
+    fn bitset_matches_naive() {
+        let input = generate_input(1_000, 0x2023_1204);
+        let naive = parse(&input).iter().map(naive_matches).collect_vec();
+        let bitset = parse_bitset(&input).iter().map(|card| card.num_matches() as usize).collect_vec();
+        assert_eq!(naive, bitset);
+    }
+
+    #[test]
+    #[ignore = "benchmark, run with --ignored --nocapture"]
+    fn bitset_benchmark() {
+        let input = generate_input(1_000_000, 0x2023_1204);
+        let cards = parse(&input);
+        let bitset_cards = parse_bitset(&input);
+
+        let start = std::time::Instant::now();
+        let naive: usize = cards.iter().map(naive_matches).sum();
+        println!("naive:  {naive} matches in {:?}", start.elapsed());
+
+        let start = std::time::Instant::now();
+        let bitset: usize = bitset_cards.iter().map(|card| card.num_matches() as usize).sum();
+        println!("bitset: {bitset} matches in {:?}", start.elapsed());
+
+        assert_eq!(naive, bitset);
+    }
+
 }
\ No newline at end of file
@@ -1,6 +1,10 @@
+use std::{collections::{BTreeSet, HashSet}, fmt::Display};
+
 use aoc_runner_derive::aoc;
 use itertools::Itertools;
-use nom::{IResult, combinator::{map_res, all_consuming}, multi::{separated_list1, many0, many1}, character::complete::{digit1, space1, line_ending}, sequence::{tuple, pair, preceded, terminated}, bytes::complete::{tag, is_not}};
+use nom::{IResult, combinator::{map_res, map, all_consuming}, multi::{separated_list1, many0, many1}, character::complete::{digit1, space1, line_ending, alpha1}, sequence::{pair, preceded, terminated, separated_pair}, bytes::complete::tag};
+
+use crate::diagnostics::DiagnosticsReport;
 
 
 fn parse_u64(input: &str) -> IResult<&str, u64> {
@@ -58,10 +62,28 @@ fn parse_mapping_list(input: &str) -> IResult<&str, Vec<Mapping>> {
     separated_list1(line_ending, parse_mapping)(input)
 }
 
-fn parse_map(input: &str) -> IResult<&str, Vec<Mapping>> {
-    preceded(
-        tuple((is_not(" "), space1, tag("map:"), line_ending)),
-        parse_mapping_list
+#[derive(Debug, PartialEq)]
+struct AlmanacMap {
+    source: String,
+    destination: String,
+    mappings: Vec<Mapping>
+}
+
+fn parse_map_header(input: &str) -> IResult<&str, (&str, &str)> {
+    terminated(
+        separated_pair(alpha1, tag("-to-"), alpha1),
+        pair(space1, tag("map:"))
+    )(input)
+}
+
+fn parse_map(input: &str) -> IResult<&str, AlmanacMap> {
+    map(
+        pair(terminated(parse_map_header, line_ending), parse_mapping_list),
+        |((source, destination), mappings)| AlmanacMap {
+            source: source.to_string(),
+            destination: destination.to_string(),
+            mappings
+        }
     )(input)
 }
 
@@ -69,7 +91,7 @@ fn line_ending1(input: &str) -> IResult<&str, Vec<&str>> {
     many1(line_ending)(input)
 }
 
-fn parse_all_maps(input: &str) -> IResult<&str, Vec<Vec<Mapping>>> {
+fn parse_all_maps(input: &str) -> IResult<&str, Vec<AlmanacMap>> {
     separated_list1(
         line_ending1,
         parse_map
@@ -80,7 +102,7 @@ fn line_ending0(input: &str) -> IResult<&str, Vec<&str>> {
     many0(line_ending)(input)
 }
 
-fn parse(input: &str) -> (Vec<u64>, Vec<Vec<Mapping>>) {
+fn parse(input: &str) -> (Vec<u64>, Vec<AlmanacMap>) {
     let (_, result) = all_consuming(pair(
         terminated(parse_seed_list, line_ending0),
         terminated(parse_all_maps, line_ending0)
@@ -95,7 +117,7 @@ fn part1(input: &str) -> u64 {
 
     seed_list.into_iter().map(|seed| {
         maps_vec.iter().fold(seed, |src, map| {
-            map.iter().find_map(|mapping| {
+            map.mappings.iter().find_map(|mapping| {
                 if src >= mapping.src_start && src < mapping.src_start + mapping.range_len {
                     Some(src - mapping.src_start + mapping.dest_start)
                 } else {
@@ -114,7 +136,7 @@ fn part2_naive(input: &str) -> u64 {
         |(&start, &end)| (start..start+end)
     ).map(|seed| {
         maps_vec.iter().fold(seed, |src, map| {
-            map.iter().find_map(|mapping| {
+            map.mappings.iter().find_map(|mapping| {
                 if src >= mapping.src_start && src < mapping.src_start + mapping.range_len {
                     Some(src - mapping.src_start + mapping.dest_start)
                 } else {
@@ -174,12 +196,348 @@ fn process_ranges(input_pairs: &mut Vec<(u64, u64)>, mappings: &Vec<Mapping>) ->
 fn part2_fast(input: &str) -> u64 {
     let (seed_list, maps_vec) = parse(input);
 
-    maps_vec.into_iter().fold(seed_list.into_iter().tuples().collect_vec(), |mut seeds, mappings| {
-        process_ranges(&mut seeds, &mappings)
+    maps_vec.into_iter().fold(seed_list.into_iter().tuples().collect_vec(), |mut seeds, map| {
+        process_ranges(&mut seeds, &map.mappings)
     }).into_iter().map(|(range_start, _)| range_start).min().unwrap()
 }
 
 
+// a layer of mappings normalized into sorted, disjoint pieces covering the whole u64 domain,
+// identity gaps included; each piece runs from its start up to the start of the next piece
+#[derive(Debug, Clone, PartialEq)]
+struct IntervalMap {
+    pieces: Vec<(u64, i128)>
+}
+impl IntervalMap {
+    fn identity() -> Self {
+        Self { pieces: vec![(0, 0)] }
+    }
+
+    fn from_mappings(mappings: &[Mapping]) -> Self {
+        // sweep over every mapping boundary, tracking which mappings cover the current position;
+        // overlapping mappings resolve to whichever is listed first, same as process_ranges
+        let mut events = mappings.iter().enumerate().filter(|(_, mapping)| mapping.range_len > 0).flat_map(|(idx, mapping)| [
+            (mapping.src_start, true, idx),
+            (mapping.src_start.saturating_add(mapping.range_len), false, idx)
+        ]).collect_vec();
+        events.sort_unstable();
+
+        let mut active = BTreeSet::new();
+        let pieces = events.into_iter().map(|(pos, is_start, idx)| {
+            if is_start {
+                active.insert(idx);
+            } else {
+                active.remove(&idx);
+            }
+            let offset = active.first().map_or(0, |&idx| {
+                let mapping: &Mapping = &mappings[idx];
+                mapping.dest_start as i128 - mapping.src_start as i128
+            });
+            (pos, offset)
+        }).collect_vec();
+
+        Self::merged([(0, 0)].into_iter().chain(pieces))
+    }
+
+    // pieces must be sorted by start; a later piece with the same start replaces the earlier one
+    fn merged(pieces: impl IntoIterator<Item = (u64, i128)>) -> Self {
+        let mut merged: Vec<(u64, i128)> = vec![];
+        for (start, offset) in pieces {
+            if merged.last().is_some_and(|&(last_start, _)| last_start == start) {
+                merged.pop();
+            }
+            if merged.last().is_none_or(|&(_, last_offset)| last_offset != offset) {
+                merged.push((start, offset));
+            }
+        }
+        Self { pieces: merged }
+    }
+
+    fn piece_end(&self, idx: usize) -> u64 {
+        self.pieces.get(idx + 1).map_or(u64::MAX, |&(start, _)| start)
+    }
+
+    fn piece_index(&self, value: u64) -> usize {
+        self.pieces.partition_point(|&(start, _)| start <= value) - 1
+    }
+
+    fn apply(&self, value: u64) -> u64 {
+        let (_, offset) = self.pieces[self.piece_index(value)];
+        (value as i128 + offset) as u64
+    }
+
+    // the single map equivalent to applying self, then next
+    fn compose(&self, next: &IntervalMap) -> IntervalMap {
+        let mut pieces = vec![];
+        for (idx, &(start, offset)) in self.pieces.iter().enumerate() {
+            let image_start = (start as i128 + offset) as u64;
+            let image_end = (self.piece_end(idx) as i128 + offset).min(u64::MAX as i128) as u64;
+
+            let mut next_idx = next.piece_index(image_start);
+            loop {
+                let (next_start, next_offset) = next.pieces[next_idx];
+                let sub_start = image_start.max(next_start);
+                pieces.push(((sub_start as i128 - offset) as u64, offset + next_offset));
+                if next.piece_end(next_idx) >= image_end {
+                    break;
+                }
+                next_idx += 1;
+            }
+        }
+        Self::merged(pieces)
+    }
+
+    // only a layer whose images tile the domain exactly (a bijection) can be inverted
+    fn invert(&self) -> Option<IntervalMap> {
+        let mut images = self.pieces.iter().enumerate().map(|(idx, &(start, offset))| {
+            (start as i128 + offset, self.piece_end(idx) as i128 + offset, -offset)
+        }).collect_vec();
+        images.sort_unstable();
+
+        let mut expected_start = 0;
+        for &(image_start, image_end, _) in &images {
+            if image_start != expected_start {
+                return None;
+            }
+            expected_start = image_end;
+        }
+        if expected_start != u64::MAX as i128 {
+            return None;
+        }
+
+        Some(Self::merged(images.into_iter().map(|(image_start, _, offset)| (image_start as u64, offset))))
+    }
+
+    // every part of the domain that lands inside the (start, len) range, one part per piece that reaches
+    // it, so pieces sharing their images each show up
+    fn preimage(&self, (range_start, range_len): (u64, u64)) -> Vec<(u64, u64)> {
+        let range_end = range_start as i128 + range_len as i128;
+        self.pieces.iter().enumerate().filter_map(|(idx, &(start, offset))| {
+            let sub_start = (start as i128).max(range_start as i128 - offset);
+            let sub_end = (self.piece_end(idx) as i128).min(range_end - offset);
+            (sub_start < sub_end).then(|| (sub_start as u64, (sub_end - sub_start) as u64))
+        }).collect()
+    }
+
+    // pushes (start, len) ranges through the map, splitting them wherever they cross a piece boundary
+    fn map_ranges(&self, ranges: &[(u64, u64)]) -> Vec<(u64, u64)> {
+        ranges.iter().flat_map(|&(range_start, range_len)| {
+            let range_end = range_start + range_len;
+            (self.piece_index(range_start)..self.pieces.len()).map_while(move |idx| {
+                let (piece_start, offset) = self.pieces[idx];
+                let sub_start = range_start.max(piece_start);
+                (sub_start < range_end).then(|| {
+                    let sub_end = range_end.min(self.piece_end(idx));
+                    ((sub_start as i128 + offset) as u64, sub_end - sub_start)
+                })
+            })
+        }).collect()
+    }
+}
+
+fn compose_layers(maps_vec: &[AlmanacMap]) -> IntervalMap {
+    maps_vec.iter().fold(IntervalMap::identity(), |combined, map| {
+        combined.compose(&IntervalMap::from_mappings(&map.mappings))
+    })
+}
+
+#[aoc(day5, part1, interval_map)]
+fn part1_interval_map(input: &str) -> u64 {
+    let (seed_list, maps_vec) = parse(input);
+    let seed_to_location = compose_layers(&maps_vec);
+
+    seed_list.into_iter().map(|seed| seed_to_location.apply(seed)).min().unwrap()
+}
+
+#[aoc(day5, part2, interval_map)]
+fn part2_interval_map(input: &str) -> u64 {
+    let (seed_list, maps_vec) = parse(input);
+    let seed_to_location = compose_layers(&maps_vec);
+
+    seed_to_location.map_ranges(&seed_list.into_iter().tuples().collect_vec())
+        .into_iter().map(|(range_start, _)| range_start).min().unwrap()
+}
+
+
+fn intersect_ranges((a_start, a_len): (u64, u64), (b_start, b_len): (u64, u64)) -> Option<(u64, u64)> {
+    let start = a_start.max(b_start);
+    let end = (a_start + a_len).min(b_start + b_len);
+    (start < end).then(|| (start, end - start))
+}
+
+struct Almanac {
+    seeds: Vec<u64>,
+    maps: Vec<AlmanacMap>
+}
+impl Almanac {
+    fn parse(input: &str) -> Self {
+        let (seeds, maps) = parse(input);
+        Self { seeds, maps }
+    }
+
+    fn seed_ranges(&self) -> Vec<(u64, u64)> {
+        self.seeds.iter().copied().tuples().collect()
+    }
+
+    // follows the chain of maps by name from seed to the given category and collapses it into one map
+    fn seed_to(&self, category: &str) -> Result<IntervalMap, String> {
+        let mut current = "seed";
+        let mut visited = HashSet::new();
+        let mut combined = IntervalMap::identity();
+        while current != category {
+            if !visited.insert(current) {
+                return Err(format!("Maps loop back to {current} without reaching {category}"));
+            }
+            let map = self.maps.iter().find(|map| map.source == current)
+                .ok_or_else(|| format!("No map leads from {current} to {category}"))?;
+            combined = combined.compose(&IntervalMap::from_mappings(&map.mappings));
+            current = &map.destination;
+        }
+        Ok(combined)
+    }
+
+    // every part of the seed ranges whose values end up inside the given (start, len) range of the category
+    fn seeds_for(&self, category: &str, range: (u64, u64)) -> Result<Vec<(u64, u64)>, String> {
+        let seed_ranges = self.seed_ranges();
+        let seed_to_category = self.seed_to(category)?;
+        // a bijective chain turns around into a map like any other, anything else has to collect
+        // every piece that lands in the range
+        let candidates = match seed_to_category.invert() {
+            Some(category_to_seed) => category_to_seed.map_ranges(&[range]),
+            None => seed_to_category.preimage(range)
+        };
+        let mut found = candidates.into_iter().flat_map(|candidate| {
+            seed_ranges.iter().filter_map(move |&seed_range| intersect_ranges(candidate, seed_range))
+        }).collect_vec();
+        found.sort_unstable();
+        Ok(found)
+    }
+
+    // walks the category values upwards, span by span, until one maps back into a seed range. the spans
+    // are cut wherever some piece's image starts or ends, so every piece covers a span fully or not at all
+    fn lowest_reachable(&self, category: &str) -> Result<Option<u64>, String> {
+        let seed_ranges = self.seed_ranges();
+        let seed_to_category = self.seed_to(category)?;
+        let bounds = seed_to_category.pieces.iter().enumerate().flat_map(|(idx, &(start, offset))| {
+            [start as i128 + offset, seed_to_category.piece_end(idx) as i128 + offset]
+        }).map(|bound| bound.min(u64::MAX as i128) as u64).sorted_unstable().dedup().collect_vec();
+
+        for (span_start, span_end) in bounds.into_iter().tuple_windows() {
+            let lowest = seed_to_category.preimage((span_start, span_end - span_start)).into_iter().flat_map(|candidate| {
+                seed_ranges.iter().filter_map(move |&seed_range| intersect_ranges(candidate, seed_range))
+            }).map(|(seed_start, _)| seed_to_category.apply(seed_start)).min();
+            if lowest.is_some() {
+                return Ok(lowest);
+            }
+        }
+        Ok(None)
+    }
+}
+
+#[aoc(day5, part2, reverse)]
+fn part2_reverse(input: &str) -> u64 {
+    let almanac = Almanac::parse(input);
+
+    almanac.lowest_reachable("location").unwrap().unwrap()
+}
+
+// the lowest location along with the seeds that reach it
+#[aoc(day5, part2, lowest_seeds)]
+fn part2_lowest_seeds(input: &str) -> String {
+    let almanac = Almanac::parse(input);
+
+    let Some(location) = almanac.lowest_reachable("location").unwrap() else {
+        return "no location reachable".to_string();
+    };
+    let seeds = almanac.seeds_for("location", (location, 1)).unwrap().into_iter()
+        .map(|(start, len)| if len == 1 { start.to_string() } else { format!("{start}..{}", start + len - 1) })
+        .join(", ");
+    format!("{location} from seeds {seeds}")
+}
+
+
+#[derive(Debug, PartialEq)]
+enum AlmanacDiagnostic {
+    InvalidLine { line: usize },
+    RangeOverflow { line: usize },
+    OverlappingMappings { line: usize, other_line: usize },
+    BrokenChain { line: usize, expected: String, found: String }
+}
+impl AlmanacDiagnostic {
+    fn line(&self) -> usize {
+        match self {
+            Self::InvalidLine { line } | Self::RangeOverflow { line }
+                | Self::OverlappingMappings { line, .. } | Self::BrokenChain { line, .. } => *line
+        }
+    }
+}
+impl Display for AlmanacDiagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidLine { line } => write!(f, "line {line}: not a seed list, map header or mapping"),
+            Self::RangeOverflow { line } => write!(f, "line {line}: mapping range overflows u64"),
+            Self::OverlappingMappings { line, other_line } => write!(f, "line {line}: source range overlaps mapping on line {other_line}"),
+            Self::BrokenChain { line, expected, found } => write!(f, "line {line}: map starts from {found}, expected {expected}")
+        }
+    }
+}
+
+// walks the input line by line so every problem can be reported against where it appears
+fn validate(input: &str) -> Vec<AlmanacDiagnostic> {
+    let mut diagnostics = vec![];
+    let mut layers: Vec<Vec<(usize, Mapping)>> = vec![];
+    let mut expected_source = "seed".to_string();
+
+    for (idx, text) in input.lines().enumerate() {
+        let line = idx + 1;
+        if text.is_empty() {
+            continue;
+        }
+        if idx == 0 {
+            if all_consuming(parse_seed_list)(text).is_err() {
+                diagnostics.push(AlmanacDiagnostic::InvalidLine { line });
+            }
+            continue;
+        }
+
+        if let Ok((_, (source, destination))) = all_consuming(parse_map_header)(text) {
+            if source != expected_source {
+                diagnostics.push(AlmanacDiagnostic::BrokenChain { line, expected: expected_source, found: source.to_string() });
+            }
+            expected_source = destination.to_string();
+            layers.push(vec![]);
+        } else if let (Some(layer), Ok((_, mapping))) = (layers.last_mut(), all_consuming(parse_mapping)(text)) {
+            if mapping.src_start.checked_add(mapping.range_len).is_none() || mapping.dest_start.checked_add(mapping.range_len).is_none() {
+                diagnostics.push(AlmanacDiagnostic::RangeOverflow { line });
+            }
+            layer.push((line, mapping));
+        } else {
+            diagnostics.push(AlmanacDiagnostic::InvalidLine { line });
+        }
+    }
+
+    // widen to u128 so overflowing mappings can still be checked for overlaps
+    let src_range = |mapping: &Mapping| (mapping.src_start as u128, mapping.src_start as u128 + mapping.range_len as u128);
+    for layer in layers {
+        for ((other_line, other), (line, mapping)) in layer.iter().tuple_combinations() {
+            let (other_start, other_end) = src_range(other);
+            let (start, end) = src_range(mapping);
+            if start < other_end && other_start < end {
+                diagnostics.push(AlmanacDiagnostic::OverlappingMappings { line: *line, other_line: *other_line });
+            }
+        }
+    }
+
+    diagnostics.sort_by_key(AlmanacDiagnostic::line);
+    diagnostics
+}
+
+#[aoc(day5, part1, validate)]
+fn part1_validate(input: &str) -> DiagnosticsReport<AlmanacDiagnostic> {
+    DiagnosticsReport(validate(input))
+}
+
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -189,10 +547,14 @@ mod tests {
         assert_eq!(parse_seed_list("seeds: 79 14 55 13"), Ok(("", vec![79, 14, 55, 13])));
         assert_eq!(parse_map("seed-to-soil map:
 50 98 2
-52 50 48"), Ok(("", vec![
-            Mapping::from((50, 98, 2)),
-            Mapping::from((52, 50, 48))
-        ])));
+52 50 48"), Ok(("", AlmanacMap {
+            source: "seed".to_string(),
+            destination: "soil".to_string(),
+            mappings: vec![
+                Mapping::from((50, 98, 2)),
+                Mapping::from((52, 50, 48))
+            ]
+        })));
     }
 
     #[test]
@@ -335,4 +697,212 @@ humidity-to-location map:
 "), 46);
     }
 
+    const EXAMPLE: &str = "seeds: 79 14 55 13
+
+seed-to-soil map:
+50 98 2
+52 50 48
+
+soil-to-fertilizer map:
+0 15 37
+37 52 2
+39 0 15
+
+fertilizer-to-water map:
+49 53 8
+0 11 42
+42 0 7
+57 7 4
+
+water-to-light map:
+88 18 7
+18 25 70
+
+light-to-temperature map:
+45 77 23
+81 45 19
+68 64 13
+
+temperature-to-humidity map:
+0 69 1
+1 0 69
+
+humidity-to-location map:
+60 56 37
+56 93 4";
+
+    #[test]
+    fn interval_map_from_mappings() {
+        assert_eq!(IntervalMap::from_mappings(&[
+            Mapping::from((50, 98, 2)),
+            Mapping::from((52, 50, 48))
+        ]), IntervalMap { pieces: vec![(0, 0), (50, 2), (98, -48), (100, 0)] });
+        // overlaps resolve to the first listed mapping, adjacent pieces with equal offsets merge
+        assert_eq!(IntervalMap::from_mappings(&[
+            Mapping::from((110, 10, 10)),
+            Mapping::from((0, 15, 10)),
+            Mapping::from((120, 20, 5))
+        ]), IntervalMap { pieces: vec![(0, 0), (10, 100), (20, -15), (25, 0)] });
+    }
+
+    #[test]
+    fn interval_map_compose() {
+        let (_, maps_vec) = parse(EXAMPLE);
+        let seed_to_location = compose_layers(&maps_vec);
+        for seed in 0..200 {
+            let expected = maps_vec.iter().fold(seed, |src, map| IntervalMap::from_mappings(&map.mappings).apply(src));
+            assert_eq!(seed_to_location.apply(seed), expected);
+        }
+    }
+
+    #[test]
+    fn interval_map_invert() {
+        let (_, maps_vec) = parse(EXAMPLE);
+        let seed_to_location = compose_layers(&maps_vec);
+        let location_to_seed = seed_to_location.invert().unwrap();
+        for seed in 0..200 {
+            assert_eq!(location_to_seed.apply(seed_to_location.apply(seed)), seed);
+        }
+        assert_eq!(seed_to_location.compose(&location_to_seed), IntervalMap::identity());
+
+        // two sources landing on the same destination have no inverse
+        assert_eq!(IntervalMap::from_mappings(&[Mapping::from((0, 10, 5))]).invert(), None);
+    }
+
+    #[test]
+    fn interval_map_preimage() {
+        let layer = IntervalMap::from_mappings(&[
+            Mapping::from((50, 98, 2)),
+            Mapping::from((52, 50, 48))
+        ]);
+        assert_eq!(layer.preimage((81, 14)), vec![(79, 14)]);
+        assert_eq!(layer.preimage((45, 10)), vec![(45, 5), (50, 3), (98, 2)]);
+
+        // 10..15 and the identity gap 0..5 both land on 0..5
+        let folded = IntervalMap::from_mappings(&[Mapping::from((0, 10, 5))]);
+        assert_eq!(folded.invert(), None);
+        assert_eq!(folded.preimage((2, 5)), vec![(2, 5), (12, 3)]);
+        assert_eq!(folded.preimage((10, 3)), vec![]);
+    }
+
+    #[test]
+    fn interval_map_ranges() {
+        let layer = IntervalMap::from_mappings(&[
+            Mapping::from((50, 98, 2)),
+            Mapping::from((52, 50, 48))
+        ]);
+        assert_eq!(layer.map_ranges(&[(79, 14), (55, 13)]), vec![(81, 14), (57, 13)]);
+        assert_eq!(layer.map_ranges(&[(45, 60)]), vec![(45, 5), (52, 48), (50, 2), (100, 5)]);
+    }
+
+    #[test]
+    fn part1_interval_map_example() {
+        assert_eq!(part1_interval_map(EXAMPLE), 35);
+    }
+
+    #[test]
+    fn part2_interval_map_example() {
+        assert_eq!(part2_interval_map(EXAMPLE), 46);
+    }
+
+    #[test]
+    fn almanac_seed_to() {
+        let almanac = Almanac::parse(EXAMPLE);
+        let seed_to_soil = almanac.seed_to("soil").unwrap();
+        assert_eq!([79, 14, 55, 13].map(|seed| seed_to_soil.apply(seed)), [81, 14, 57, 13]);
+        let seed_to_light = almanac.seed_to("light").unwrap();
+        assert_eq!([79, 14, 55, 13].map(|seed| seed_to_light.apply(seed)), [74, 42, 46, 34]);
+        assert!(almanac.seed_to("nowhere").is_err());
+
+        let looping = Almanac::parse("seeds: 1 2
+
+seed-to-soil map:
+0 5 5
+
+soil-to-seed map:
+5 0 5");
+        assert!(looping.seed_to("water").is_err());
+        assert!(looping.seed_to("soil").is_ok());
+    }
+
+    #[test]
+    fn almanac_seeds_for() {
+        let almanac = Almanac::parse(EXAMPLE);
+        assert_eq!(almanac.seeds_for("location", (46, 1)), Ok(vec![(82, 1)]));
+        assert_eq!(almanac.seeds_for("location", (0, 46)), Ok(vec![]));
+        assert_eq!(almanac.seeds_for("soil", (81, 3)), Ok(vec![(79, 3)]));
+        assert_eq!(almanac.seeds_for("location", (60, 9)), Ok(vec![(92, 1)]));
+    }
+
+    #[test]
+    fn almanac_lowest_reachable() {
+        let almanac = Almanac::parse(EXAMPLE);
+        assert_eq!(almanac.lowest_reachable("location"), Ok(Some(46)));
+        assert_eq!(almanac.lowest_reachable("soil"), Ok(Some(57)));
+        assert_eq!(almanac.lowest_reachable("seed"), Ok(Some(55)));
+    }
+
+    #[test]
+    fn almanac_reverse_not_bijective() {
+        // seeds 10..15 move onto 0..5, where seeds 0..5 already stay
+        let input = "seeds: 3 4 12 2
+
+seed-to-soil map:
+0 10 5
+
+soil-to-location map:
+100 0 3";
+        let almanac = Almanac::parse(input);
+        assert_eq!(almanac.seeds_for("location", (100, 3)), Ok(vec![(12, 1)]));
+        assert_eq!(almanac.seeds_for("soil", (3, 2)), Ok(vec![(3, 2), (13, 1)]));
+        assert_eq!(almanac.lowest_reachable("soil"), Ok(Some(2)));
+        assert_eq!(almanac.lowest_reachable("location"), Ok(Some(3)));
+        assert_eq!(part2_lowest_seeds(input), "3 from seeds 3, 13");
+    }
+
+    #[test]
+    fn part2_reverse_example() {
+        assert_eq!(part2_reverse(EXAMPLE), 46);
+    }
+
+    #[test]
+    fn part2_lowest_seeds_example() {
+        assert_eq!(part2_lowest_seeds(EXAMPLE), "46 from seeds 82");
+    }
+
+    #[test]
+    fn validate_example() {
+        assert_eq!(validate(EXAMPLE), vec![]);
+    }
+
+    #[test]
+    fn validate_problems() {
+        let diagnostics = validate("seeds: 79 14 55 13
+
+seed-to-soil map:
+50 98 2
+52 50 48
+0 60 5
+
+soil-to-fertilizer map:
+0 18446744073709551610 10
+what is this
+
+seed-to-water map:
+0 0 1");
+        assert_eq!(diagnostics, vec![
+            AlmanacDiagnostic::OverlappingMappings { line: 6, other_line: 5 },
+            AlmanacDiagnostic::RangeOverflow { line: 9 },
+            AlmanacDiagnostic::InvalidLine { line: 10 },
+            AlmanacDiagnostic::BrokenChain { line: 12, expected: "fertilizer".to_string(), found: "seed".to_string() }
+        ]);
+        assert_eq!(diagnostics[0].to_string(), "line 6: source range overlaps mapping on line 5");
+    }
+
+    #[test]
+    fn part1_validate_report() {
+        assert_eq!(part1_validate(EXAMPLE).to_string(), "no problems found");
+        assert_eq!(part1_validate("seeds: 1 2\n\nseed-to-soil map:\n1 2\n0 0 1").to_string(), "\nline 4: not a seed list, map header or mapping");
+    }
+
 }
\ No newline at end of file
@@ -1,6 +1,7 @@
 use anyhow::Result;
 use aoc_runner_derive::aoc;
 use itertools::Itertools;
+use num::{BigUint, Integer, integer::Roots};
 use nom::{IResult, combinator::{map_res, all_consuming}, multi::{separated_list1, fold_many1}, character::complete::{digit1, space1, alpha1, char, line_ending}, sequence::{preceded, tuple, separated_pair, pair}};
 
 
@@ -55,14 +56,22 @@ fn parse_part1(input: &str) -> impl Iterator<Item = Race> {
     times.into_iter().zip_eq(distances.into_iter()).map(|r| r.into())
 }
 
-fn parse_separated_u64(input: &str) -> IResult<&str, u64> {
-    map_res(fold_many1(
+fn parse_separated_digits(input: &str) -> IResult<&str, String> {
+    fold_many1(
         preceded(space1, digit1),
         String::new,
         |acc, digits| {
             acc + digits
         }
-    ), |num| u64::from_str_radix(&num, 10))(input)
+    )(input)
+}
+
+fn parse_separated_u64(input: &str) -> IResult<&str, u64> {
+    map_res(parse_separated_digits, |num| u64::from_str_radix(&num, 10))(input)
+}
+
+fn parse_separated_biguint(input: &str) -> IResult<&str, BigUint> {
+    map_res(parse_separated_digits, |num| num.parse::<BigUint>())(input)
 }
 
 fn parse_bad_kerning_line(input: &str) -> IResult<&str, u64> {
@@ -72,6 +81,13 @@ fn parse_bad_kerning_line(input: &str) -> IResult<&str, u64> {
     )(input)
 }
 
+fn parse_bad_kerning_line_big(input: &str) -> IResult<&str, BigUint> {
+    preceded(
+        pair(alpha1, char(':')),
+        parse_separated_biguint
+    )(input)
+}
+
 fn parse_part2(input: &str) -> Race {
     let (_, race) = all_consuming(separated_pair(
         parse_bad_kerning_line,
@@ -82,6 +98,16 @@ fn parse_part2(input: &str) -> Race {
     race.into()
 }
 
+fn parse_part2_big(input: &str) -> (BigUint, BigUint) {
+    let (_, race) = all_consuming(separated_pair(
+        parse_bad_kerning_line_big,
+        line_ending,
+        parse_bad_kerning_line_big
+    ))(input).unwrap();
+
+    race
+}
+
 #[aoc(day6, part1)]
 fn part1(input: &str) -> u64 {
     let races = parse_part1(input);
@@ -107,9 +133,172 @@ fn part2(input: &str) -> u64 {
 }
 
 
+// the hold time t wins when t * (T - t) > D, so the winners sit strictly between the roots
+// of t^2 - T*t + D = 0; an integer square root of the discriminant gets within a step of the lower root
+fn count_winning_holds<N: Integer + Roots + Clone>(time: N, distance: N) -> N {
+    let two = N::one() + N::one();
+    let time_squared = time.clone() * time.clone();
+    let min_discriminant = two.clone() * two.clone() * distance.clone();
+    if time_squared <= min_discriminant {
+        return N::zero();
+    }
+
+    let root = (time_squared - min_discriminant).sqrt();
+    let mut min_hold = (time.clone() - root) / two.clone();
+    while min_hold.clone() * (time.clone() - min_hold.clone()) <= distance {
+        min_hold = min_hold + N::one();
+        // past the halfway point the distance only shrinks again
+        if min_hold.clone() * two.clone() > time {
+            return N::zero();
+        }
+    }
+    time - two * min_hold + N::one()
+}
+
+fn count_race_closed_form(race: &Race) -> u64 {
+    count_winning_holds(race.time as u128, race.distance as u128) as u64
+}
+
+#[aoc(day6, part1, closed_form)]
+fn part1_closed_form(input: &str) -> u64 {
+    let races = parse_part1(input);
+
+    races.map(|race| count_race_closed_form(&race)).product()
+}
+
+#[aoc(day6, part2, closed_form)]
+fn part2_closed_form(input: &str) -> BigUint {
+    let (time, distance) = parse_part2_big(input);
+
+    match (u64::try_from(&time), u64::try_from(&distance)) {
+        (Ok(time), Ok(distance)) => count_race_closed_form(&Race { time, distance }).into(),
+        _ => count_winning_holds(time, distance)
+    }
+}
+
+
+// distance covered over a whole race of `race_time` ms when the button is held for `hold` ms;
+// the solver relies on this rising strictly up to its peak and never rising again afterwards
+trait BoatPhysics {
+    fn distance(&self, hold: u64, race_time: u64) -> u128;
+}
+
+// the puzzle's own boat, speed grows by `rate` for every ms held
+struct LinearCharge {
+    rate: u64
+}
+impl BoatPhysics for LinearCharge {
+    fn distance(&self, hold: u64, race_time: u64) -> u128 {
+        self.rate as u128 * hold as u128 * (race_time - hold) as u128
+    }
+}
+
+struct QuadraticCharge {
+    rate: u64
+}
+impl BoatPhysics for QuadraticCharge {
+    fn distance(&self, hold: u64, race_time: u64) -> u128 {
+        let speed = (self.rate as u128).saturating_mul(hold as u128 * hold as u128);
+        speed.saturating_mul((race_time - hold) as u128)
+    }
+}
+
+struct CappedCharge {
+    rate: u64,
+    max_speed: u64
+}
+impl BoatPhysics for CappedCharge {
+    fn distance(&self, hold: u64, race_time: u64) -> u128 {
+        let speed = (self.rate as u128 * hold as u128).min(self.max_speed as u128);
+        speed * (race_time - hold) as u128
+    }
+}
+
+// after release the boat loses `drag` speed every ms until it comes to a stop
+struct ConstantDrag {
+    rate: u64,
+    drag: u64
+}
+impl BoatPhysics for ConstantDrag {
+    fn distance(&self, hold: u64, race_time: u64) -> u128 {
+        let speed = self.rate as u128 * hold as u128;
+        let travel_time = (race_time - hold) as u128;
+        let moving_time = if self.drag == 0 {
+            travel_time
+        } else {
+            travel_time.min(speed.div_ceil(self.drag as u128))
+        };
+        moving_time * speed - self.drag as u128 * moving_time * moving_time.saturating_sub(1) / 2
+    }
+}
+
+// first value in low..high where the predicate stops holding, assuming it holds for a prefix of the range
+fn partition_point(mut low: u64, mut high: u64, pred: impl Fn(u64) -> bool) -> u64 {
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if pred(mid) {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+    low
+}
+
+fn count_winning_holds_with(physics: &(impl BoatPhysics + ?Sized), race: &Race) -> u64 {
+    let distance = |hold| physics.distance(hold, race.time);
+    let record = race.distance as u128;
+
+    let peak = partition_point(0, race.time, |hold| distance(hold + 1) > distance(hold));
+    if distance(peak) <= record {
+        return 0;
+    }
+    let first_winner = partition_point(0, peak, |hold| distance(hold) <= record);
+    let past_last_winner = partition_point(peak, race.time + 1, |hold| distance(hold) > record);
+    past_last_winner - first_winner
+}
+
+#[aoc(day6, part1, physics)]
+fn part1_physics(input: &str) -> u64 {
+    let races = parse_part1(input);
+
+    races.map(|race| count_winning_holds_with(&LinearCharge { rate: 1 }, &race)).product()
+}
+
+#[aoc(day6, part2, physics)]
+fn part2_physics(input: &str) -> u64 {
+    let race = parse_part2(input);
+
+    count_winning_holds_with(&LinearCharge { rate: 1 }, &race)
+}
+
+// the boats raced by the models variant: the puzzle's own, one whose speed grows with the square of
+// the hold, one charging twice as fast up to 50 mm/ms, and one charging twice as fast but slowing
+// down by 1 mm/ms every ms after release
+const PUZZLE_BOAT: LinearCharge = LinearCharge { rate: 1 };
+const QUADRATIC_BOAT: QuadraticCharge = QuadraticCharge { rate: 1 };
+const CAPPED_BOAT: CappedCharge = CappedCharge { rate: 2, max_speed: 50 };
+const DRAG_BOAT: ConstantDrag = ConstantDrag { rate: 2, drag: 1 };
+
+// the same races under each of the other boat models, for comparing how they change the answer
+#[aoc(day6, part1, models)]
+fn part1_models(input: &str) -> String {
+    let races = parse_part1(input).collect_vec();
+    let ways = |physics: &dyn BoatPhysics| races.iter().map(|race| count_winning_holds_with(physics, race)).product::<u64>();
+
+    [
+        ("linear", ways(&PUZZLE_BOAT)),
+        ("quadratic", ways(&QUADRATIC_BOAT)),
+        ("capped", ways(&CAPPED_BOAT)),
+        ("drag", ways(&DRAG_BOAT))
+    ].into_iter().map(|(model, ways)| format!("{model}: {ways}")).join(", ")
+}
+
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_rng::TestRng;
 
     #[test]
     fn parser_test() {
@@ -133,4 +322,91 @@ Distance:  9  40  200"), 288);
 Distance:  9  40  200"), 71503);
     }
 
+    #[test]
+    fn part1_closed_form_example() {
+        assert_eq!(part1_closed_form("Time:      7  15   30
+Distance:  9  40  200"), 288);
+    }
+
+    #[test]
+    fn part2_closed_form_example() {
+        assert_eq!(part2_closed_form("Time:      7  15   30
+Distance:  9  40  200"), BigUint::from(71503u32));
+    }
+
+    #[test]
+    fn closed_form_edge_cases() {
+        assert_eq!(count_winning_holds(2u128, 0), 1);
+        assert_eq!(count_winning_holds(3u128, 2), 0);
+        assert_eq!(count_winning_holds(4u128, 3), 1);
+        assert_eq!(count_winning_holds(4u128, 4), 0);
+        assert_eq!(count_winning_holds(0u128, 0), 0);
+    }
+
+    #[test]
+    fn closed_form_matches_scan() {
+        let mut rng = TestRng::new(0x2023_1206);
+        let mut next_num = |bound: u64| rng.below(bound as usize) as u64;
+        for _ in 0..1_000 {
+            // keep a winner below the halfway point so the scan in part1 always finds one
+            let time = next_num(10_000) + 4;
+            let below_half = time / 2 - 1;
+            let distance = next_num(below_half * (time - below_half));
+            let race = Race { time, distance };
+
+            let scan = part1(&format!("Time: {time}\nDistance: {distance}"));
+            assert_eq!(count_race_closed_form(&race), scan, "{race:?}");
+        }
+    }
+
+    #[test]
+    fn closed_form_beyond_u64() {
+        // with D = h * (T - h), the winners are exactly h + 1 ..= T - h - 1
+        let time: BigUint = "300000000000000000000000000000".parse().unwrap();
+        let hold: BigUint = "12345678901234567890123".parse().unwrap();
+        let distance = hold.clone() * (time.clone() - hold.clone());
+        let expected = time.clone() - hold * 2u32 - 1u32;
+
+        assert_eq!(count_winning_holds(time.clone(), distance.clone()), expected);
+        assert_eq!(part2_closed_form(&format!("Time: {time}\nDistance: {distance}")), expected);
+    }
+
+    #[test]
+    fn part1_physics_example() {
+        assert_eq!(part1_physics("Time:      7  15   30
+Distance:  9  40  200"), 288);
+    }
+
+    #[test]
+    fn part1_models_example() {
+        assert_eq!(part1_models("Time:      7  15   30
+Distance:  9  40  200"), "linear: 288, quadratic: 1755, capped: 1584, drag: 765");
+    }
+
+    #[test]
+    fn part2_physics_example() {
+        assert_eq!(part2_physics("Time:      7  15   30
+Distance:  9  40  200"), 71503);
+    }
+
+    fn check_against_brute_force(physics: &impl BoatPhysics) {
+        for time in 0..60 {
+            let best = (0..=time).map(|hold| physics.distance(hold, time)).max().unwrap();
+            for distance in [0, best / 3, best / 2, best.saturating_sub(1), best] {
+                let race = Race { time, distance: distance as u64 };
+                let brute_force = (0..=time).filter(|&hold| physics.distance(hold, time) > distance).count() as u64;
+                assert_eq!(count_winning_holds_with(physics, &race), brute_force, "{race:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn physics_models_match_brute_force() {
+        check_against_brute_force(&LinearCharge { rate: 3 });
+        check_against_brute_force(&QuadraticCharge { rate: 2 });
+        check_against_brute_force(&CappedCharge { rate: 2, max_speed: 25 });
+        check_against_brute_force(&ConstantDrag { rate: 4, drag: 3 });
+        check_against_brute_force(&ConstantDrag { rate: 1, drag: 0 });
+    }
+
 }
\ No newline at end of file
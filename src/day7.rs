@@ -1,25 +1,26 @@
-use std::cmp::Ordering;
+use std::{cmp::Ordering, marker::PhantomData, collections::BTreeMap, fmt::Display};
 
 use aoc_runner_derive::aoc;
 use itertools::Itertools;
 use nom::{IResult, combinator::{map_res, all_consuming}, multi::separated_list1, character::complete::{digit1, space1, line_ending}, sequence::separated_pair, bytes::complete::take};
 
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+// only the label printed on the card, how strong it is depends on the rules in play
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 enum Card {
-    Ace = 14,
-    King = 13,
-    Queen = 12,
-    Jack = 11,
-    Ten = 10,
-    Nine = 9,
-    Eight = 8,
-    Seven = 7,
-    Six = 6,
-    Five = 5,
-    Four = 4,
-    Three = 3,
-    Two = 2
+    Ace,
+    King,
+    Queen,
+    Jack,
+    Ten,
+    Nine,
+    Eight,
+    Seven,
+    Six,
+    Five,
+    Four,
+    Three,
+    Two
 }
 impl TryFrom<char> for Card {
     type Error = &'static str;
@@ -48,8 +49,27 @@ impl TryFrom<u8> for Card {
         Self::try_from(char::from(value))
     }
 }
+impl From<Card> for char {
+    fn from(value: Card) -> Self {
+        match value {
+            Card::Ace => 'A',
+            Card::King => 'K',
+            Card::Queen => 'Q',
+            Card::Jack => 'J',
+            Card::Ten => 'T',
+            Card::Nine => '9',
+            Card::Eight => '8',
+            Card::Seven => '7',
+            Card::Six => '6',
+            Card::Five => '5',
+            Card::Four => '4',
+            Card::Three => '3',
+            Card::Two => '2'
+        }
+    }
+}
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
 enum HandType {
     FiveOfAKind = 7,
     FourOfAKind = 6,
@@ -59,74 +79,121 @@ enum HandType {
     OnePair = 2,
     HighCard = 1
 }
+impl HandType {
+    // groups are the sizes of each set of matching non-wild cards, largest first
+    fn from_groups(groups: &[usize], wildcards: usize) -> Self {
+        // wildcards always do the most good joining the largest group
+        let largest = groups.first().copied().unwrap_or(0) + wildcards;
+        let second = groups.get(1).copied().unwrap_or(0);
+
+        // hand types only care about the two largest groups, anything past five of a kind still counts as five
+        match (largest, second) {
+            (5.., _) => Self::FiveOfAKind,
+            (4, _) => Self::FourOfAKind,
+            (3, 2..) => Self::FullHouse,
+            (3, _) => Self::ThreeOfAKind,
+            (2, 2) => Self::TwoPair,
+            (2, _) => Self::OnePair,
+            _ => Self::HighCard
+        }
+    }
+}
+
+// describes a variant of Camel Cards, the card strengths, which cards are wild and how many make a hand
+trait CamelRules {
+    // every label allowed in a hand, weakest first
+    const RANKING: &'static [Card];
+    // wildcards act like whichever label makes the strongest hand type
+    const WILDCARDS: &'static [Card];
+    const HAND_SIZE: usize;
+
+    fn strength(card: &Card) -> usize {
+        Self::RANKING.iter().position(|ranked| ranked == card).unwrap()
+    }
+}
+
+struct StandardRules;
+impl CamelRules for StandardRules {
+    const RANKING: &'static [Card] = &[
+        Card::Two, Card::Three, Card::Four, Card::Five, Card::Six, Card::Seven, Card::Eight,
+        Card::Nine, Card::Ten, Card::Jack, Card::Queen, Card::King, Card::Ace
+    ];
+    const WILDCARDS: &'static [Card] = &[];
+    const HAND_SIZE: usize = 5;
+}
+
+struct JokerRules;
+impl CamelRules for JokerRules {
+    const RANKING: &'static [Card] = &[
+        Card::Jack, Card::Two, Card::Three, Card::Four, Card::Five, Card::Six, Card::Seven,
+        Card::Eight, Card::Nine, Card::Ten, Card::Queen, Card::King, Card::Ace
+    ];
+    const WILDCARDS: &'static [Card] = &[Card::Jack];
+    const HAND_SIZE: usize = 5;
+}
 
 #[derive(Debug)]
-struct Hand {
+struct Hand<R: CamelRules> {
     cards: Vec<Card>,
-    bet: u32
+    hand_type: HandType,
+    bet: u32,
+    rules: PhantomData<R>
 }
-impl Hand {
-    fn get_hand_type(&self) -> HandType {
-        let cards_map = self.cards.iter().counts();
-        match cards_map.values().max().unwrap() {
-            5 => HandType::FiveOfAKind,
-            4 => HandType::FourOfAKind,
-            3 => {
-                // do we have a pair to go along with the three matched cards?
-                match cards_map.values().min().unwrap() {
-                    2 => HandType::FullHouse,
-                    1 => HandType::ThreeOfAKind,
-                    _ => panic!("Expected between 1 and 2 additional cards to match with 3 already matching")
-                }
-            },
-            2 => {
-                // do we have two pairs or just one?
-                match cards_map.values().filter(|&&c| c == 2).count() {
-                    2 => HandType::TwoPair,
-                    1 => HandType::OnePair,
-                    _ => panic!("Expected between 1 and 2 pairs of matched cards")
-                }
-            },
-            1 => HandType::HighCard,
-            _ => panic!("Expected between 1 and 5 cards to match in a hand")
+impl<R: CamelRules> Hand<R> {
+    fn get_hand_type(cards: &[Card]) -> HandType {
+        let wildcards = cards.iter().filter(|card| R::WILDCARDS.contains(card)).count();
+        let groups = cards.iter().filter(|card| !R::WILDCARDS.contains(card)).counts()
+            .into_values().sorted_unstable_by(|a, b| b.cmp(a)).collect_vec();
+        HandType::from_groups(&groups, wildcards)
+    }
+
+    // the label every wildcard in the hand stands in for, picking the strongest label among the largest groups
+    fn wildcard_substitution(&self) -> Option<Card> {
+        if !self.cards.iter().any(|card| R::WILDCARDS.contains(card)) {
+            return None;
         }
+        let best = self.cards.iter().filter(|card| !R::WILDCARDS.contains(card)).counts().into_iter()
+            .max_by_key(|&(card, count)| (count, R::strength(card)))
+            .map(|(&card, _)| card);
+        best.or_else(|| R::RANKING.iter().rev().find(|card| !R::WILDCARDS.contains(card)).copied())
+    }
+
+    // first card position where this hand differs from another
+    fn tiebreak_position(&self, other: &Self) -> Option<usize> {
+        self.cards.iter().zip_eq(other.cards.iter()).position(|(card, other_card)| card != other_card)
     }
 }
-impl TryFrom<(&str, u32)> for Hand {
+impl<R: CamelRules> TryFrom<(&str, u32)> for Hand<R> {
     type Error = &'static str;
     fn try_from((chars, bet): (&str, u32)) -> Result<Self, Self::Error> {
-        if chars.len() != 5 {
-            Err("expected five cards in a hand")
+        if chars.len() != R::HAND_SIZE {
+            Err("wrong number of cards in a hand")
         } else {
-            let cards = chars.bytes().map(|b|
-                TryInto::<Card>::try_into(b)
-            ).collect::<Result<_, _>>()?;
-            Ok(Self {cards, bet})
+            let cards: Vec<Card> = chars.bytes().map(Card::try_from).collect::<Result<_, _>>()?;
+            if !cards.iter().all(|card| R::RANKING.contains(card)) {
+                return Err("card not used by these rules");
+            }
+            let hand_type = Self::get_hand_type(&cards);
+            Ok(Self {cards, hand_type, bet, rules: PhantomData})
         }
     }
 }
-impl PartialEq for Hand {
+impl<R: CamelRules> PartialEq for Hand<R> {
     fn eq(&self, other: &Self) -> bool {
         self.cards == other.cards
     }
 }
-impl Eq for Hand {}
-impl PartialOrd for Hand {
+impl<R: CamelRules> Eq for Hand<R> {}
+impl<R: CamelRules> PartialOrd for Hand<R> {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         Some(self.cmp(other))
     }
 }
-impl Ord for Hand {
+impl<R: CamelRules> Ord for Hand<R> {
     fn cmp(&self, other: &Self) -> Ordering {
-        match self.get_hand_type().cmp(&other.get_hand_type()) {
-            Ordering::Equal => {},
-            ord => return ord,
-        };
-        self.cards.iter().zip_eq(other.cards.iter()).map(|(card, other_card)| {
-            card.cmp(other_card)
-        }).find_or_last(|&order| {
-            order != Ordering::Equal
-        }).unwrap()
+        self.hand_type.cmp(&other.hand_type).then_with(|| {
+            self.cards.iter().map(R::strength).cmp(other.cards.iter().map(R::strength))
+        })
     }
 }
 
@@ -138,27 +205,23 @@ fn parse_u32(input: &str) -> IResult<&str, u32> {
     )(input)
 }
 
-fn parse_line(input: &str) -> IResult<&str, Hand> {
+fn parse_line<R: CamelRules>(input: &str) -> IResult<&str, Hand<R>> {
     map_res(separated_pair(
-        take(5u32),
+        take(R::HAND_SIZE),
         space1,
         parse_u32
     ), |h| h.try_into())(input)
 }
 
-fn parse_part1(input: &str) -> Vec<Hand> {
+fn parse<R: CamelRules>(input: &str) -> Vec<Hand<R>> {
     let (_, hands) = all_consuming(
-        separated_list1(line_ending, parse_line)
+        separated_list1(line_ending, parse_line::<R>)
     )(input).unwrap();
 
     hands
 }
 
-
-#[aoc(day7, part1)]
-fn part1(input: &str) -> u32 {
-    let mut hands = parse_part1(input);
-    
+fn total_winnings<R: CamelRules>(mut hands: Vec<Hand<R>>) -> u32 {
     hands.sort_unstable();
 
     hands.into_iter().enumerate().fold(0, |acc, (ix, hand)| {
@@ -167,163 +230,158 @@ fn part1(input: &str) -> u32 {
 }
 
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
-enum JokerCard {
-    Ace = 14,
-    King = 13,
-    Queen = 12,
-    Ten = 10,
-    Nine = 9,
-    Eight = 8,
-    Seven = 7,
-    Six = 6,
-    Five = 5,
-    Four = 4,
-    Three = 3,
-    Two = 2,
-    Joker = 1,
-}
-impl TryFrom<char> for JokerCard {
-    type Error = &'static str;
-    fn try_from(value: char) -> Result<Self, Self::Error> {
-        match value {
-            'A' => Ok(Self::Ace),
-            'K' => Ok(Self::King),
-            'Q' => Ok(Self::Queen),
-            'J' => Ok(Self::Joker),
-            'T' => Ok(Self::Ten),
-            '9' => Ok(Self::Nine),
-            '8' => Ok(Self::Eight),
-            '7' => Ok(Self::Seven),
-            '6' => Ok(Self::Six),
-            '5' => Ok(Self::Five),
-            '4' => Ok(Self::Four),
-            '3' => Ok(Self::Three),
-            '2' => Ok(Self::Two),
-            _ => Err("bad card value")
-        }
-    }
+#[aoc(day7, part1)]
+fn part1(input: &str) -> u32 {
+    total_winnings(parse::<StandardRules>(input))
 }
-impl TryFrom<u8> for JokerCard {
-    type Error = &'static str;
-    fn try_from(value: u8) -> Result<Self, Self::Error> {
-        Self::try_from(char::from(value))
-    }
+
+#[aoc(day7, part2)]
+fn part2(input: &str) -> u32 {
+    total_winnings(parse::<JokerRules>(input))
 }
 
-#[derive(Debug)]
-struct JokerHand {
-    cards: Vec<JokerCard>,
+
+#[derive(Debug, PartialEq)]
+struct RankedHand {
+    cards: Vec<Card>,
     hand_type: HandType,
+    wildcards_as: Option<Card>,
+    rank: u32,
+    // the card position that put this hand above the one ranked just below it, when both share a hand type
+    tiebreak: Option<usize>,
     bet: u32
 }
-impl JokerHand {
-    fn get_hand_type(cards: &Vec<JokerCard>) -> HandType {
-        let cards_map = cards.iter().counts();
-        let joker_count = *cards_map.get(&JokerCard::Joker).unwrap_or(&0);
-        let joker_filter = |(&k, &v)| {
-            if k != &JokerCard::Joker {
-                Some(v)
-            } else {
-                None
+
+#[derive(Debug)]
+struct WinningsReport {
+    hands: Vec<RankedHand>,
+    hand_type_counts: BTreeMap<HandType, usize>,
+    // what the same number of randomly dealt hands would give on average
+    expected_hand_types: BTreeMap<HandType, f64>,
+    expected_winnings: f64,
+    total_winnings: u32
+}
+impl<R: CamelRules> From<Vec<Hand<R>>> for WinningsReport {
+    fn from(mut hands: Vec<Hand<R>>) -> Self {
+        hands.sort_unstable();
+
+        let ranked = hands.iter().enumerate().map(|(ix, hand)| {
+            let tiebreak = ix.checked_sub(1).map(|below| &hands[below])
+                .filter(|below| below.hand_type == hand.hand_type)
+                .and_then(|below| hand.tiebreak_position(below));
+            RankedHand {
+                cards: hand.cards.clone(),
+                hand_type: hand.hand_type,
+                wildcards_as: hand.wildcard_substitution(),
+                rank: 1 + ix as u32,
+                tiebreak,
+                bet: hand.bet
             }
-        };
-        match cards_map.iter().filter_map(joker_filter).max().unwrap_or(0) + joker_count {
-            5 => HandType::FiveOfAKind,
-            4 => HandType::FourOfAKind,
-            3 => {
-                // do we have a pair to go along with the three matched cards?
-                match cards_map.iter().filter_map(joker_filter).min().unwrap() {
-                    2 => HandType::FullHouse,
-                    1 => HandType::ThreeOfAKind,
-                    _ => panic!("Expected between 1 and 2 additional cards to match with 3 already matching")
-                }
-            },
-            2 => {
-                if joker_count != 0 {
-                    // if we got to a max of 2 matched cards when adding jokers,
-                    // we must have 1 joker with a hand that would otherwise be a HighCard
-                    HandType::OnePair
-                } else {
-                    // do we have two pairs or just one?
-                    match cards_map.iter().filter_map(joker_filter).filter(|&c| c == 2).count() {
-                        2 => HandType::TwoPair,
-                        1 => HandType::OnePair,
-                        _ => panic!("Expected between 1 and 2 pairs of matched cards")
-                    }
-                }
-            },
-            1 => HandType::HighCard,
-            _ => panic!("Expected between 1 and 5 cards to match in a hand")
+        }).collect_vec();
+
+        Self {
+            hand_type_counts: ranked.iter().map(|hand| hand.hand_type).counts().into_iter().collect(),
+            expected_hand_types: hand_type_probabilities::<R>().into_iter()
+                .map(|(hand_type, probability)| (hand_type, probability * ranked.len() as f64)).collect(),
+            expected_winnings: expected_winnings(&ranked.iter().map(|hand| hand.bet).collect_vec()),
+            total_winnings: ranked.iter().map(|hand| hand.bet * hand.rank).sum(),
+            hands: ranked
         }
     }
 }
-impl TryFrom<(&str, u32)> for JokerHand {
-    type Error = &'static str;
-    fn try_from((chars, bet): (&str, u32)) -> Result<Self, Self::Error> {
-        if chars.len() != 5 {
-            Err("expected five cards in a hand")
-        } else {
-            let cards = chars.bytes().map(|b|
-                TryInto::<JokerCard>::try_into(b)
-            ).collect::<Result<_, _>>()?;
-            let hand_type = Self::get_hand_type(&cards);
-            Ok(Self {cards, bet, hand_type})
+impl Display for WinningsReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f)?;
+        writeln!(f, "{:>6}  {:<8}  {:<12}  {:>4}  {:>8}  {:>6}  {:>10}", "rank", "hand", "type", "wild", "tiebreak", "bet", "winnings")?;
+        for hand in &self.hands {
+            writeln!(f, "{:>6}  {:<8}  {:<12}  {:>4}  {:>8}  {:>6}  {:>10}",
+                hand.rank,
+                hand.cards.iter().map(|&card| char::from(card)).collect::<String>(),
+                format!("{:?}", hand.hand_type),
+                hand.wildcards_as.map_or('-', char::from),
+                hand.tiebreak.map_or("-".to_string(), |position| position.to_string()),
+                hand.bet,
+                hand.bet * hand.rank
+            )?;
         }
+        writeln!(f)?;
+        writeln!(f, "{:<12}  {:>6}  {:>8}", "type", "hands", "expected")?;
+        for (hand_type, expected) in self.expected_hand_types.iter().rev() {
+            let count = self.hand_type_counts.get(hand_type).copied().unwrap_or(0);
+            writeln!(f, "{:<12}  {:>6}  {:>8.1}  {}", format!("{hand_type:?}"), count, expected, "#".repeat(count * 50 / self.hands.len()))?;
+        }
+        writeln!(f)?;
+        writeln!(f, "expected winnings: {:.1}", self.expected_winnings)?;
+        write!(f, "total winnings: {}", self.total_winnings)
     }
 }
-impl PartialEq for JokerHand {
-    fn eq(&self, other: &Self) -> bool {
-        self.cards == other.cards
-    }
+
+#[aoc(day7, part1, report)]
+fn part1_report(input: &str) -> WinningsReport {
+    WinningsReport::from(parse::<StandardRules>(input))
 }
-impl Eq for JokerHand {}
-impl PartialOrd for JokerHand {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
-    }
+
+#[aoc(day7, part2, report)]
+fn part2_report(input: &str) -> WinningsReport {
+    WinningsReport::from(parse::<JokerRules>(input))
 }
-impl Ord for JokerHand {
-    fn cmp(&self, other: &Self) -> Ordering {
-        match self.hand_type.cmp(&other.hand_type) {
-            Ordering::Equal => {},
-            ord => return ord,
-        };
-        self.cards.iter().zip_eq(other.cards.iter()).map(|(card, other_card)| {
-            card.cmp(other_card)
-        }).find_or_last(|&order| {
-            order != Ordering::Equal
-        }).unwrap()
+
+
+
+// every way to split `total` cards into groups no larger than `max_group`, largest group first
+fn group_partitions(total: usize, max_group: usize) -> Vec<Vec<usize>> {
+    if total == 0 {
+        return vec![vec![]];
     }
+    (1..=total.min(max_group)).rev().flat_map(|group| {
+        group_partitions(total - group, group).into_iter().map(move |mut rest| {
+            rest.insert(0, group);
+            rest
+        })
+    }).collect()
 }
 
-fn parse_joker_line(input: &str) -> IResult<&str, JokerHand> {
-    map_res(separated_pair(
-        take(5u32),
-        space1,
-        parse_u32
-    ), |h| h.try_into())(input)
+fn factorial(n: usize) -> u64 {
+    (1..=n as u64).product()
 }
 
-fn parse_part2(input: &str) -> Vec<JokerHand> {
-    let (_, hands) = all_consuming(
-        separated_list1(line_ending, parse_joker_line)
-    )(input).unwrap();
+// number of hands of each type out of every hand drawn with replacement from the ranked labels,
+// counted from the shape of the hand rather than by listing each hand
+fn hand_type_counts<R: CamelRules>() -> BTreeMap<HandType, u64> {
+    let labels = R::RANKING.iter().filter(|card| !R::WILDCARDS.contains(card)).count();
+    let wild_labels = (R::RANKING.len() - labels) as u64;
 
-    hands
-}
+    let mut counts = BTreeMap::new();
+    for wildcards in 0..=R::HAND_SIZE {
+        let others = R::HAND_SIZE - wildcards;
+        // choose which positions hold wildcards, and which wild label sits in each
+        let wild_ways = factorial(R::HAND_SIZE) / (factorial(wildcards) * factorial(others)) * wild_labels.pow(wildcards as u32);
+        if wild_ways == 0 {
+            continue;
+        }
+        for groups in group_partitions(others, others).into_iter().filter(|groups| groups.len() <= labels) {
+            // give each group its own label, where groups of the same size are interchangeable
+            let label_ways = (labels - groups.len() + 1..=labels).map(|n| n as u64).product::<u64>()
+                / groups.iter().counts().into_values().map(factorial).product::<u64>();
+            // then spread the grouped cards over the remaining positions
+            let arrangements = factorial(others) / groups.iter().map(|&group| factorial(group)).product::<u64>();
 
-#[aoc(day7, part2)]
-fn part2(input: &str) -> u32 {
-    let mut hands = parse_part2(input);
-    
-    hands.sort_unstable();
+            *counts.entry(HandType::from_groups(&groups, wildcards)).or_insert(0) += wild_ways * label_ways * arrangements;
+        }
+    }
+    counts
+}
 
-    hands.into_iter().enumerate().fold(0, |acc, (ix, hand)| {
-        acc + (hand.bet * (1 + ix as u32))
-    })
+fn hand_type_probabilities<R: CamelRules>() -> BTreeMap<HandType, f64> {
+    let total_hands = (R::RANKING.len() as f64).powi(R::HAND_SIZE as i32);
+    hand_type_counts::<R>().into_iter().map(|(hand_type, count)| (hand_type, count as f64 / total_hands)).collect()
 }
 
+// independently drawn hands are interchangeable, so whatever the rules each one expects the middle rank,
+// with identical hands splitting the ranks they share evenly
+fn expected_winnings(bets: &[u32]) -> f64 {
+    bets.iter().map(|&bet| bet as f64).sum::<f64>() * (bets.len() + 1) as f64 / 2.0
+}
 
 #[cfg(test)]
 mod tests {
@@ -347,4 +405,186 @@ KTJJT 220
 QQQJA 483"), 5905);
     }
 
+    #[test]
+    fn hand_types() {
+        let hand_type = |chars| Hand::<StandardRules>::try_from((chars, 0)).unwrap().hand_type;
+        assert_eq!(hand_type("AAAAA"), HandType::FiveOfAKind);
+        assert_eq!(hand_type("AA8AA"), HandType::FourOfAKind);
+        assert_eq!(hand_type("23332"), HandType::FullHouse);
+        assert_eq!(hand_type("TTT98"), HandType::ThreeOfAKind);
+        assert_eq!(hand_type("23432"), HandType::TwoPair);
+        assert_eq!(hand_type("A23A4"), HandType::OnePair);
+        assert_eq!(hand_type("23456"), HandType::HighCard);
+        assert_eq!(hand_type("JJJJJ"), HandType::FiveOfAKind);
+
+        let joker_type = |chars| Hand::<JokerRules>::try_from((chars, 0)).unwrap().hand_type;
+        assert_eq!(joker_type("QJJQ2"), HandType::FourOfAKind);
+        assert_eq!(joker_type("JJJJJ"), HandType::FiveOfAKind);
+        assert_eq!(joker_type("2345J"), HandType::OnePair);
+        assert_eq!(joker_type("2235J"), HandType::ThreeOfAKind);
+        assert_eq!(joker_type("2233J"), HandType::FullHouse);
+        assert_eq!(joker_type("KTJJT"), HandType::FourOfAKind);
+    }
+
+    struct DeucesWild;
+    impl CamelRules for DeucesWild {
+        const RANKING: &'static [Card] = StandardRules::RANKING;
+        const WILDCARDS: &'static [Card] = &[Card::Two];
+        const HAND_SIZE: usize = 5;
+    }
+
+    struct TwoWildcards;
+    impl CamelRules for TwoWildcards {
+        const RANKING: &'static [Card] = &[
+            Card::Jack, Card::Queen, Card::Two, Card::Three, Card::Four, Card::Five, Card::Six,
+            Card::Seven, Card::Eight, Card::Nine, Card::Ten, Card::King, Card::Ace
+        ];
+        const WILDCARDS: &'static [Card] = &[Card::Jack, Card::Queen];
+        const HAND_SIZE: usize = 5;
+    }
+
+    struct SixCardHands;
+    impl CamelRules for SixCardHands {
+        const RANKING: &'static [Card] = JokerRules::RANKING;
+        const WILDCARDS: &'static [Card] = JokerRules::WILDCARDS;
+        const HAND_SIZE: usize = 6;
+    }
+
+    #[test]
+    fn configured_variants() {
+        assert_eq!(Hand::<DeucesWild>::try_from(("2K2K3", 0)).unwrap().hand_type, HandType::FourOfAKind);
+        assert!(Hand::<DeucesWild>::try_from(("2KKK4", 0)).unwrap() > Hand::<DeucesWild>::try_from(("3KKK4", 0)).unwrap());
+        assert!(Hand::<DeucesWild>::try_from(("KKK24", 0)).unwrap() > Hand::<DeucesWild>::try_from(("2KKK4", 0)).unwrap());
+
+        assert_eq!(Hand::<TwoWildcards>::try_from(("QJ2A3", 0)).unwrap().hand_type, HandType::ThreeOfAKind);
+        assert!(Hand::<TwoWildcards>::try_from(("2345Q", 0)).unwrap() > Hand::<TwoWildcards>::try_from(("2345J", 0)).unwrap());
+
+        assert_eq!(Hand::<SixCardHands>::try_from(("333444", 0)).unwrap().hand_type, HandType::FullHouse);
+        assert_eq!(Hand::<SixCardHands>::try_from(("33344J", 0)).unwrap().hand_type, HandType::FourOfAKind);
+        assert!(Hand::<SixCardHands>::try_from(("33344", 0)).is_err());
+        assert_eq!(total_winnings(parse::<SixCardHands>("333444 10
+A23456 1
+JJJJJJ 100")), 1 + 20 + 300);
+    }
+
+    #[test]
+    fn part2_report_example() {
+        let report = part2_report("32T3K 765
+T55J5 684
+KK677 28
+KTJJT 220
+QQQJA 483");
+        assert_eq!(report.total_winnings, 5905);
+        assert_eq!(report.hand_type_counts, BTreeMap::from([
+            (HandType::OnePair, 1),
+            (HandType::TwoPair, 1),
+            (HandType::FourOfAKind, 3)
+        ]));
+        assert_eq!(report.hands.iter().map(|hand| (hand.rank, hand.wildcards_as, hand.tiebreak)).collect_vec(), vec![
+            (1, None, None),
+            (2, None, None),
+            (3, Some(Card::Five), None),
+            (4, Some(Card::Queen), Some(0)),
+            (5, Some(Card::Ten), Some(0))
+        ]);
+        assert_eq!(report.hands[4], RankedHand {
+            cards: vec![Card::King, Card::Ten, Card::Jack, Card::Jack, Card::Ten],
+            hand_type: HandType::FourOfAKind,
+            wildcards_as: Some(Card::Ten),
+            rank: 5,
+            tiebreak: Some(0),
+            bet: 220
+        });
+        assert!(report.to_string().lines().any(|line| line.split_whitespace().eq(["5", "KTJJT", "FourOfAKind", "T", "0", "220", "1100"])));
+        assert!(report.to_string().lines().any(|line| line.split_whitespace().eq(["HighCard", "0", "1.3"])));
+        assert!(report.to_string().ends_with("expected winnings: 6540.0\ntotal winnings: 5905"));
+    }
+
+    #[test]
+    fn report_tiebreak_positions() {
+        let report = part1_report("KK677 1
+KTJJT 2
+33332 3
+2AAAA 4
+JJJJJ 5");
+        assert_eq!(report.hands.iter().map(|hand| hand.tiebreak).collect_vec(), vec![None, Some(1), None, Some(0), None]);
+        assert_eq!(part2_report("JJJJJ 5").hands[0].wildcards_as, Some(Card::Ace));
+    }
+
+    // the same table as hand_type_counts, built by classifying every possible hand
+    fn enumerate_hand_type_counts<R: CamelRules>() -> BTreeMap<HandType, u64> {
+        itertools::repeat_n(R::RANKING.iter().copied(), R::HAND_SIZE).multi_cartesian_product()
+            .map(|cards| Hand::<R>::get_hand_type(&cards))
+            .counts().into_iter().map(|(hand_type, count)| (hand_type, count as u64)).collect()
+    }
+
+    #[test]
+    fn hand_type_counts_standard() {
+        let counts = hand_type_counts::<StandardRules>();
+        assert_eq!(counts[&HandType::FiveOfAKind], 13);
+        assert_eq!(counts[&HandType::FourOfAKind], 13 * 12 * 5);
+        assert_eq!(counts[&HandType::FullHouse], 13 * 12 * 10);
+        assert_eq!(counts[&HandType::HighCard], 13 * 12 * 11 * 10 * 9);
+        assert_eq!(counts.values().sum::<u64>(), 13u64.pow(5));
+        assert_eq!(counts, enumerate_hand_type_counts::<StandardRules>());
+
+        let probabilities = hand_type_probabilities::<StandardRules>();
+        assert!((probabilities.values().sum::<f64>() - 1.0).abs() < 1e-9);
+        assert!((probabilities[&HandType::OnePair] - (13 * 10 * 12 * 11 * 10) as f64 / 13f64.powi(5)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn hand_type_counts_jokers() {
+        let counts = hand_type_counts::<JokerRules>();
+        assert_eq!(counts.values().sum::<u64>(), 13u64.pow(5));
+        assert_eq!(counts, enumerate_hand_type_counts::<JokerRules>());
+        // a joker can never leave a hand as two pair or high card
+        assert_eq!(counts[&HandType::HighCard], 12 * 11 * 10 * 9 * 8);
+        assert_eq!(counts[&HandType::TwoPair], hand_type_counts::<StandardRules>()[&HandType::TwoPair] * 12 * 11 * 10 / (13 * 12 * 11));
+    }
+
+    #[test]
+    fn joker_hand_type_oracle() {
+        // trying the hand's other labels in place of the jokers and keeping the best standard hand must agree with
+        // the joker rules; any label not already in the hand could only ever make a weaker hand
+        for cards in itertools::repeat_n(JokerRules::RANKING.iter().copied(), 5).multi_cartesian_product() {
+            if !cards.contains(&Card::Jack) {
+                continue;
+            }
+            let labels = cards.iter().copied().filter(|&card| card != Card::Jack).chain([Card::Ace]).unique().collect_vec();
+            let best = labels.into_iter().map(|label| {
+                let substituted = cards.iter().map(|&card| if card == Card::Jack { label } else { card }).collect_vec();
+                Hand::<StandardRules>::get_hand_type(&substituted)
+            }).max().unwrap();
+            assert_eq!(Hand::<JokerRules>::get_hand_type(&cards), best, "{cards:?}");
+        }
+    }
+
+    struct TinyRules;
+    impl CamelRules for TinyRules {
+        const RANKING: &'static [Card] = &[Card::Jack, Card::Two, Card::Ace];
+        const WILDCARDS: &'static [Card] = &[Card::Jack];
+        const HAND_SIZE: usize = 2;
+    }
+
+    #[test]
+    fn expected_winnings_exhaustive() {
+        let bets = [3, 10, 250];
+        let hands = itertools::repeat_n(TinyRules::RANKING.iter().copied(), 2).multi_cartesian_product()
+            .map(|cards| Hand::<TinyRules> { hand_type: Hand::<TinyRules>::get_hand_type(&cards), cards, bet: 0, rules: PhantomData })
+            .collect_vec();
+
+        let mut total = 0.0;
+        let mut deals = 0;
+        for dealt in itertools::repeat_n(hands.iter(), bets.len()).multi_cartesian_product() {
+            total += dealt.iter().zip(bets).map(|(hand, bet)| {
+                let beaten = dealt.iter().filter(|other| **other < *hand).count() as f64;
+                let tied = dealt.iter().filter(|other| **other == *hand).count() as f64 - 1.0;
+                bet as f64 * (1.0 + beaten + tied / 2.0)
+            }).sum::<f64>();
+            deals += 1;
+        }
+        assert!((total / deals as f64 - expected_winnings(&bets)).abs() < 1e-9);
+    }
+
 }
\ No newline at end of file
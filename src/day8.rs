@@ -1,6 +1,8 @@
-use std::{collections::HashMap, ops::ControlFlow, cell::RefCell, rc::{Rc, Weak}};
+use std::{collections::{HashMap, HashSet, VecDeque}, fmt::{Display, Write}};
 
 use aoc_runner_derive::aoc;
+use itertools::Itertools;
+use num::integer::{ExtendedGcd, Integer};
 use nom::{IResult, combinator::{map_res, all_consuming, map}, multi::{separated_list1, many_till, many1}, character::complete::{line_ending, one_of}, bytes::complete::{take, tag}, sequence::{tuple, separated_pair}};
 
 
@@ -45,37 +47,6 @@ impl TryFrom<&str> for NodeType {
     }
 }
 
-#[derive(Debug, Clone)]
-struct Node {
-    name: String,
-    location: Option<NodeType>,
-    left: RefCell<Option<Weak<Node>>>,
-    right: RefCell<Option<Weak<Node>>>,
-}
-impl From<ParsedNode> for Node {
-    fn from(value: ParsedNode) -> Self {
-        Self::from(value.name.as_str())
-    }
-}
-impl From<&str> for Node {
-    fn from(value: &str) -> Self {
-        Self {
-            location: value.try_into().ok(),
-            name: value.to_string(),
-            left: RefCell::new(None),
-            right: RefCell::new(None),
-        }
-    }
-}
-impl Node {
-    fn get(&self, dir: Direction) -> Rc<Node> {
-        match dir {
-            Direction::Left => Weak::clone(&self.left.borrow().as_ref().expect("node missing left")).upgrade().expect("failed to upgrade left"),
-            Direction::Right => Weak::clone(&self.right.borrow().as_ref().expect("node missing right")).upgrade().expect("failed to upgrade right"),
-        }
-    }
-}
-
 fn parse_directions(input: &str) -> IResult<&str, Vec<Direction>> {
     map(many_till(map_res(
         one_of("LR"),
@@ -120,70 +91,314 @@ fn parse(input: &str) ->(Vec<Direction>, Vec<ParsedNode>) {
 }
 
 
-#[aoc(day8, part1)]
-fn part1(input: &str) -> u32 {
-    let (directions, nodes) = parse(input);
-    let mut node_map: HashMap<String, Rc<Node>> = HashMap::with_capacity(nodes.len());
-    nodes.into_iter().for_each(|node| {
-        let left = Rc::downgrade(node_map.entry(node.left.clone()).or_insert(Rc::new(node.left.as_str().into())));
-        let right = Rc::downgrade(node_map.entry(node.right.clone()).or_insert(Rc::new(node.right.as_str().into())));
-
-        let new_node = node_map.entry(node.name.clone()).or_insert(Rc::new(node.name.as_str().into()));
-        new_node.left.replace(Some(left));
-        new_node.right.replace(Some(right));
-    });
+// a node's three letter name packed into a single integer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+struct Label(u32);
+impl TryFrom<&str> for Label {
+    type Error = &'static str;
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.as_bytes() {
+            &[a, b, c] => Ok(Self(u32::from_be_bytes([0, a, b, c]))),
+            _ => Err("node labels must be three bytes")
+        }
+    }
+}
+impl Display for Label {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let [_, a, b, c] = self.0.to_be_bytes();
+        write!(f, "{}{}{}", char::from(a), char::from(b), char::from(c))
+    }
+}
 
-    let starting_node = Rc::clone(node_map.get("AAA").unwrap());
+// every node lives at an index into flat arrays, links between nodes are just indices
+#[derive(Debug)]
+struct Network {
+    directions: Vec<Direction>,
+    labels: Vec<Label>,
+    indices: HashMap<Label, u32>,
+    locations: Vec<Option<NodeType>>,
+    links: Vec<[u32; 2]>,
+    // partial_passes[r][node] is where following the first r instructions from node leads
+    partial_passes: Vec<Vec<u32>>,
+    // pass_jumps[k][node] is where following every instruction 2^k times over from node leads
+    pass_jumps: Vec<Vec<u32>>
+}
+impl Network {
+    fn new(directions: Vec<Direction>, nodes: &[ParsedNode]) -> Self {
+        let labels = nodes.iter().map(|node| Label::try_from(node.name.as_str()).unwrap()).collect_vec();
+        let indices: HashMap<Label, u32> = labels.iter().enumerate().map(|(ix, &label)| (label, ix as u32)).collect();
+        let locations = nodes.iter().map(|node| NodeType::try_from(node.name.as_str()).ok()).collect();
+        let links = nodes.iter().map(|node| [
+            indices[&Label::try_from(node.left.as_str()).unwrap()],
+            indices[&Label::try_from(node.right.as_str()).unwrap()]
+        ]).collect_vec();
+
+        let mut partial_passes = vec![(0..labels.len() as u32).collect_vec()];
+        for &dir in &directions {
+            let next = partial_passes.last().unwrap().iter().map(|&node| Self::follow(&links, node, dir)).collect();
+            partial_passes.push(next);
+        }
+        let full_pass = partial_passes.pop().unwrap();
+
+        // enough doublings to cover any number of steps that fits in a u64
+        let mut pass_jumps = vec![full_pass];
+        while pass_jumps.len() < 64 {
+            let last = pass_jumps.last().unwrap();
+            let next = last.iter().map(|&node| last[node as usize]).collect();
+            pass_jumps.push(next);
+        }
 
-    let ControlFlow::Break(steps) = directions.iter().cycle().try_fold((0, starting_node), |(steps, curr_node), &dir| {
-        if curr_node.name == "ZZZ" {
-            ControlFlow::Break(steps)
-        } else {
-            ControlFlow::Continue((steps + 1, curr_node.get(dir)))
+        Self { directions, labels, indices, locations, links, partial_passes, pass_jumps }
+    }
+
+    fn follow(links: &[[u32; 2]], node: u32, dir: Direction) -> u32 {
+        match dir {
+            Direction::Left => links[node as usize][0],
+            Direction::Right => links[node as usize][1]
         }
-    }) else {
-        panic!("broke out of infinite loop without result?");
-    };
+    }
+
+    fn index(&self, name: &str) -> Option<u32> {
+        self.indices.get(&Label::try_from(name).ok()?).copied()
+    }
 
-    steps
+    fn location(&self, node: u32) -> Option<NodeType> {
+        self.locations[node as usize]
+    }
+
+    // the node reached after `step` has been taken, i.e. following the instruction at index step % len
+    fn step(&self, node: u32, step: u64) -> u32 {
+        let dir = self.directions[(step % self.directions.len() as u64) as usize];
+        Self::follow(&self.links, node, dir)
+    }
+
+    // where walking `steps` steps from `node` leads, starting from the first instruction
+    fn after_steps(&self, node: u32, steps: u64) -> u32 {
+        let passes = steps / self.directions.len() as u64;
+        let remainder = (steps % self.directions.len() as u64) as usize;
+        let node = self.pass_jumps.iter().enumerate()
+            .filter(|&(bit, _)| passes >> bit & 1 == 1)
+            .fold(node, |node, (_, jump)| jump[node as usize]);
+        self.partial_passes[remainder][node as usize]
+    }
+
+    fn steps_until(&self, start: u32, done: impl Fn(u32) -> bool) -> u64 {
+        let mut node = start;
+        let mut steps = 0;
+        while !done(node) {
+            node = self.step(node, steps);
+            steps += 1;
+        }
+        steps
+    }
+}
+
+
+#[aoc(day8, part1)]
+fn part1(input: &str) -> u64 {
+    let (directions, nodes) = parse(input);
+    let network = Network::new(directions, &nodes);
+
+    let end = network.index("ZZZ").unwrap();
+    network.steps_until(network.index("AAA").unwrap(), |node| node == end)
 }
 
 
 #[aoc(day8, part2)]
 fn part2(input: &str) -> u64 {
     let (directions, nodes) = parse(input);
-    let mut node_map: HashMap<String, Rc<Node>> = HashMap::with_capacity(nodes.len());
-    let mut starting_nodes = Vec::new();
-    nodes.into_iter().for_each(|node| {
-        let left = Rc::downgrade(node_map.entry(node.left.clone()).or_insert(Rc::new(node.left.as_str().into())));
-        let right = Rc::downgrade(node_map.entry(node.right.clone()).or_insert(Rc::new(node.right.as_str().into())));
+    let network = Network::new(directions, &nodes);
+
+    (0..network.labels.len() as u32).filter(|&node| network.location(node) == Some(NodeType::Starting)).map(|start| {
+        network.steps_until(start, |node| network.location(node) == Some(NodeType::Ending))
+    }).reduce(num::integer::lcm).unwrap()
+}
+
+// the whole network as graphviz DOT, with the part 1 walk from AAA to ZZZ highlighted
+#[aoc(day8, part1, dot)]
+fn part1_dot(input: &str) -> String {
+    let (directions, nodes) = parse(input);
+    let network = Network::new(directions, &nodes);
+
+    let (start, end) = (network.index("AAA").unwrap(), network.index("ZZZ").unwrap());
+    let steps = network.steps_until(start, |node| node == end);
+    format!("\n{}", network.to_dot(&network.walk(start, steps)))
+}
+
 
-        let new_node = node_map.entry(node.name.clone()).or_insert(Rc::new(node.name.as_str().into()));
-        new_node.left.replace(Some(left));
-        new_node.right.replace(Some(right));
+// a ghost's walk over (node, instruction index) states, which has to repeat eventually
+#[derive(Debug, PartialEq)]
+struct GhostCycle {
+    // steps taken before reaching the first state that repeats
+    prefix: u64,
+    cycle_len: u64,
+    // steps on which the ghost stands on an ending node before the cycle starts, these never recur
+    prefix_exits: Vec<u64>,
+    // steps within the first pass of the cycle on which the ghost stands on an ending node
+    cycle_exits: Vec<u64>
+}
+impl GhostCycle {
+    fn is_exit(&self, step: u64) -> bool {
+        if step < self.prefix {
+            self.prefix_exits.contains(&step)
+        } else {
+            self.cycle_exits.contains(&(self.prefix + (step - self.prefix) % self.cycle_len))
+        }
+    }
+}
 
-        if let Some(NodeType::Starting) = new_node.location {
-            starting_nodes.push(Rc::clone(new_node));
+fn find_cycle(network: &Network, start: u32) -> GhostCycle {
+    let num_instructions = network.directions.len();
+    let mut first_seen = vec![None; network.labels.len() * num_instructions];
+    let mut exits = vec![];
+    let mut node = start;
+    let mut step = 0u64;
+    loop {
+        let state = node as usize * num_instructions + (step % num_instructions as u64) as usize;
+        if let Some(prefix) = first_seen[state] {
+            let (prefix_exits, cycle_exits) = exits.into_iter().partition(|&exit| exit < prefix);
+            return GhostCycle { prefix, cycle_len: step - prefix, prefix_exits, cycle_exits };
         }
+        first_seen[state] = Some(step);
+
+        if network.location(node) == Some(NodeType::Ending) {
+            exits.push(step);
+        }
+        node = network.step(node, step);
+        step += 1;
+    }
+}
+
+// merges t = a1 (mod m1) and t = a2 (mod m2) into a single congruence, if they can both hold
+fn crt((a1, m1): (i128, i128), (a2, m2): (i128, i128)) -> Option<(i128, i128)> {
+    let ExtendedGcd { gcd, x, .. } = m1.extended_gcd(&m2);
+    if (a2 - a1) % gcd != 0 {
+        return None;
+    }
+    let lcm = m1 / gcd * m2;
+    let k = ((a2 - a1) / gcd * x).rem_euclid(m2 / gcd);
+    Some(((a1 + m1 * k).rem_euclid(lcm), lcm))
+}
+
+// the first step on which every ghost stands on an ending node at once, if there ever is one
+fn first_common_exit(ghosts: &[GhostCycle]) -> Option<u64> {
+    // a step inside some ghost's prefix has to be one of that ghost's one-off exits
+    let one_off = ghosts.iter().flat_map(|ghost| ghost.prefix_exits.iter().copied())
+        .filter(|&step| ghosts.iter().all(|ghost| ghost.is_exit(step)))
+        .min();
+
+    // past every prefix, each ghost exits on a fixed set of residues modulo its cycle length
+    let earliest = ghosts.iter().map(|ghost| ghost.prefix).max().unwrap_or(0) as i128;
+    let residues = ghosts.iter().fold(vec![(0, 1)], |combined, ghost| {
+        let cycle_len = ghost.cycle_len as i128;
+        combined.into_iter().cartesian_product(ghost.cycle_exits.iter())
+            .filter_map(|(residue, &exit)| crt(residue, (exit as i128 % cycle_len, cycle_len)))
+            .unique().collect_vec()
     });
+    let periodic = residues.into_iter()
+        .map(|(residue, modulus)| (earliest + (residue - earliest).rem_euclid(modulus)) as u64)
+        .min();
+
+    one_off.into_iter().chain(periodic).min()
+}
+
+#[aoc(day8, part2, cycles)]
+fn part2_cycles(input: &str) -> String {
+    let (directions, nodes) = parse(input);
+    let network = Network::new(directions, &nodes);
+
+    let ghosts = (0..network.labels.len() as u32).filter(|&node| network.location(node) == Some(NodeType::Starting))
+        .map(|start| find_cycle(&network, start))
+        .collect_vec();
+
+    first_common_exit(&ghosts).map_or("never".to_string(), |steps| steps.to_string())
+}
+
+// jumps every ghost straight to the step where they all first stand on an exit, to show which exit
+// each one ends up on
+#[aoc(day8, part2, exits)]
+fn part2_exits(input: &str) -> String {
+    let (directions, nodes) = parse(input);
+    let network = Network::new(directions, &nodes);
 
-    starting_nodes.into_iter().map(|node| {
-        if let ControlFlow::Break(fold_val) = directions.iter().cycle().try_fold(
-            (node, 0u64),
-            |(curr_node, steps), &dir| {
-                if let Some(NodeType::Ending) = curr_node.location {
-                    ControlFlow::Break(steps)
-                } else {
-                    ControlFlow::Continue((curr_node.get(dir), steps + 1))
+    let starts = (0..network.labels.len() as u32).filter(|&node| network.location(node) == Some(NodeType::Starting)).collect_vec();
+    let ghosts = starts.iter().map(|&start| find_cycle(&network, start)).collect_vec();
+
+    first_common_exit(&ghosts).map_or("never".to_string(), |steps| {
+        starts.iter().map(|&start| {
+            format!("{} -> {}", network.labels[start as usize], network.labels[network.after_steps(start, steps) as usize])
+        }).join(", ")
+    })
+}
+
+
+impl Network {
+    // every node visited walking `steps` steps from `start`, including the start itself
+    fn walk(&self, start: u32, steps: u64) -> Vec<u32> {
+        let mut path = vec![start];
+        for step in 0..steps {
+            path.push(self.step(*path.last().unwrap(), step));
+        }
+        path
+    }
+
+    fn reachable_from(&self, start: u32) -> HashSet<u32> {
+        let mut seen = HashSet::from([start]);
+        let mut queue = VecDeque::from([start]);
+        while let Some(node) = queue.pop_front() {
+            for next in self.links[node as usize] {
+                if seen.insert(next) {
+                    queue.push_back(next);
                 }
-        }) {
-            fold_val
-        } else {
-            panic!("broke out of infinite loop without result?");
+            }
         }
-    }).reduce(num::integer::lcm).unwrap()
-}
+        seen
+    }
 
+    // renders the network for graphviz, one cluster per ghost holding everything it can reach
+    // (a node reachable by several ghosts goes to the first), with the edges along `walk` highlighted
+    fn to_dot(&self, walk: &[u32]) -> String {
+        let walked: HashSet<(u32, u32)> = walk.iter().copied().tuple_windows().collect();
+        let node_line = |node: u32| {
+            let style = match self.location(node) {
+                Some(NodeType::Starting) => " [style=filled, fillcolor=palegreen]",
+                Some(NodeType::Ending) => " [style=filled, fillcolor=salmon]",
+                None => ""
+            };
+            format!("\"{}\"{}", self.labels[node as usize], style)
+        };
+
+        let mut output = String::new();
+        writeln!(output, "digraph network {{").unwrap();
+        writeln!(output, "    node [shape=circle];").unwrap();
+
+        let mut placed = HashSet::new();
+        let starts = (0..self.labels.len() as u32).filter(|&node| self.location(node) == Some(NodeType::Starting));
+        for (ghost, start) in starts.enumerate() {
+            let component = self.reachable_from(start).into_iter().filter(|&node| placed.insert(node)).sorted().collect_vec();
+            writeln!(output, "    subgraph cluster_{ghost} {{").unwrap();
+            writeln!(output, "        label=\"{}\";", self.labels[start as usize]).unwrap();
+            for node in component {
+                writeln!(output, "        {};", node_line(node)).unwrap();
+            }
+            writeln!(output, "    }}").unwrap();
+        }
+        for node in (0..self.labels.len() as u32).filter(|node| !placed.contains(node)) {
+            writeln!(output, "    {};", node_line(node)).unwrap();
+        }
+
+        for (node, &[left, right]) in self.links.iter().enumerate() {
+            let node = node as u32;
+            let edges = if left == right { vec![("LR", left)] } else { vec![("L", left), ("R", right)] };
+            for (label, next) in edges {
+                let highlight = if walked.contains(&(node, next)) { ", color=red, penwidth=2" } else { "" };
+                writeln!(output, "    \"{}\" -> \"{}\" [label=\"{label}\"{highlight}];", self.labels[node as usize], self.labels[next as usize]).unwrap();
+            }
+        }
+        writeln!(output, "}}").unwrap();
+        output
+    }
+}
 
 #[cfg(test)]
 mod tests {
@@ -222,4 +437,205 @@ ZZZ = (ZZZ, ZZZ)"), 6);
 XXX = (XXX, XXX)"), 6);
     }
 
+    #[test]
+    fn part2_cycles_example() {
+        assert_eq!(part2_cycles("LR
+
+11A = (11B, XXX)
+11B = (XXX, 11Z)
+11Z = (11B, XXX)
+22A = (22B, XXX)
+22B = (22C, 22C)
+22C = (22Z, 22Z)
+22Z = (22B, 22B)
+XXX = (XXX, XXX)"), "6");
+    }
+
+    #[test]
+    fn find_cycle_example() {
+        let (directions, nodes) = parse("LR
+
+11A = (11B, XXX)
+11B = (XXX, 11Z)
+11Z = (11B, XXX)
+XXX = (XXX, XXX)");
+        let network = Network::new(directions, &nodes);
+        assert_eq!(find_cycle(&network, 0), GhostCycle {
+            prefix: 1,
+            cycle_len: 2,
+            prefix_exits: vec![],
+            cycle_exits: vec![2]
+        });
+    }
+
+    #[test]
+    fn part2_exits_example() {
+        assert_eq!(part2_exits("LR
+
+11A = (11B, XXX)
+11B = (XXX, 11Z)
+11Z = (11B, XXX)
+22A = (22B, XXX)
+22B = (22C, 22C)
+22C = (22Z, 22Z)
+22Z = (22B, 22B)
+XXX = (XXX, XXX)"), "11A -> 11Z, 22A -> 22Z");
+        assert_eq!(part2_exits("L
+
+11A = (11B, 11B)
+11B = (11A, 11A)"), "never");
+    }
+
+    #[test]
+    fn part2_cycles_offset_exits() {
+        // the first ghost exits on every odd step, the second on steps 2, 5, 8, ...
+        // so the lcm of the first exits (2) is wrong, the real answer is 5
+        assert_eq!(part2_cycles("L
+
+11A = (11Z, 11Z)
+11Z = (11B, 11B)
+11B = (11Z, 11Z)
+22A = (22B, 22B)
+22B = (22Z, 22Z)
+22Z = (22C, 22C)
+22C = (22B, 22B)"), "5");
+    }
+
+    #[test]
+    fn part2_cycles_never() {
+        // odd steps against even steps
+        assert_eq!(part2_cycles("L
+
+11A = (11Z, 11Z)
+11Z = (11B, 11B)
+11B = (11Z, 11Z)
+22A = (22B, 22B)
+22B = (22Z, 22Z)
+22Z = (22B, 22B)"), "never");
+        // no ending node reachable at all
+        assert_eq!(part2_cycles("L
+
+11A = (11B, 11B)
+11B = (11A, 11A)"), "never");
+    }
+
+    #[test]
+    fn part2_cycles_prefix_exit() {
+        // the second ghost only passes an ending node once, on step 1, before settling into a loop
+        assert_eq!(part2_cycles("L
+
+11A = (11Z, 11Z)
+11Z = (11Z, 11Z)
+22A = (22Z, 22Z)
+22Z = (22B, 22B)
+22B = (22B, 22B)"), "1");
+    }
+
+    #[test]
+    fn label_packing() {
+        let label = Label::try_from("AZ1").unwrap();
+        assert_eq!(label, Label(u32::from_be_bytes([0, b'A', b'Z', b'1'])));
+        assert_eq!(label.to_string(), "AZ1");
+        assert!(Label::try_from("ABCD").is_err());
+    }
+
+    #[test]
+    fn after_steps_matches_walking() {
+        let (directions, nodes) = parse("LLR
+
+AAA = (BBB, BBB)
+BBB = (AAA, ZZZ)
+ZZZ = (ZZZ, ZZZ)
+11A = (11B, XXX)
+11B = (XXX, 11Z)
+11Z = (11B, XXX)
+XXX = (11A, 11Z)");
+        let network = Network::new(directions, &nodes);
+        for start in 0..network.labels.len() as u32 {
+            let mut node = start;
+            for steps in 0..50 {
+                assert_eq!(network.after_steps(start, steps), node);
+                node = network.step(node, steps);
+            }
+        }
+    }
+
+    #[test]
+    fn after_steps_huge() {
+        // a walk that alternates between two nodes on every step, whatever the instructions say
+        let (directions, nodes) = parse("LRR
+
+AAA = (BBB, BBB)
+BBB = (AAA, AAA)");
+        let network = Network::new(directions, &nodes);
+        let start = network.index("AAA").unwrap();
+        assert_eq!(network.after_steps(start, 1_000_000_000_000_000_000), start);
+        assert_eq!(network.after_steps(start, u64::MAX), network.index("BBB").unwrap());
+    }
+
+    #[test]
+    fn to_dot_example() {
+        let (directions, nodes) = parse("LR
+
+11A = (11B, XXX)
+11B = (XXX, 11Z)
+11Z = (11B, XXX)
+22A = (22B, XXX)
+22B = (22C, 22C)
+22C = (22Z, 22Z)
+22Z = (22B, 22B)
+XXX = (XXX, XXX)
+YYY = (XXX, 11A)");
+        let network = Network::new(directions, &nodes);
+        let start = network.index("11A").unwrap();
+        let walk = network.walk(start, 3);
+        assert_eq!(walk.iter().map(|&node| network.labels[node as usize].to_string()).collect_vec(), ["11A", "11B", "11Z", "11B"]);
+
+        assert_eq!(network.to_dot(&walk), r#"digraph network {
+    node [shape=circle];
+    subgraph cluster_0 {
+        label="11A";
+        "11A" [style=filled, fillcolor=palegreen];
+        "11B";
+        "11Z" [style=filled, fillcolor=salmon];
+        "XXX";
+    }
+    subgraph cluster_1 {
+        label="22A";
+        "22A" [style=filled, fillcolor=palegreen];
+        "22B";
+        "22C";
+        "22Z" [style=filled, fillcolor=salmon];
+    }
+    "YYY";
+    "11A" -> "11B" [label="L", color=red, penwidth=2];
+    "11A" -> "XXX" [label="R"];
+    "11B" -> "XXX" [label="L"];
+    "11B" -> "11Z" [label="R", color=red, penwidth=2];
+    "11Z" -> "11B" [label="L", color=red, penwidth=2];
+    "11Z" -> "XXX" [label="R"];
+    "22A" -> "22B" [label="L"];
+    "22A" -> "XXX" [label="R"];
+    "22B" -> "22C" [label="LR"];
+    "22C" -> "22Z" [label="LR"];
+    "22Z" -> "22B" [label="LR"];
+    "XXX" -> "XXX" [label="LR"];
+    "YYY" -> "XXX" [label="L"];
+    "YYY" -> "11A" [label="R"];
+}
+"#);
+    }
+
+    #[test]
+    fn part1_dot_example() {
+        let dot = part1_dot("LLR
+
+AAA = (BBB, BBB)
+BBB = (AAA, ZZZ)
+ZZZ = (ZZZ, ZZZ)");
+        assert!(dot.contains("    \"AAA\" -> \"BBB\" [label=\"LR\", color=red, penwidth=2];"));
+        assert!(dot.contains("    \"BBB\" -> \"ZZZ\" [label=\"R\", color=red, penwidth=2];"));
+        assert!(dot.contains("    \"ZZZ\" -> \"ZZZ\" [label=\"LR\"];"));
+    }
+
 }
\ No newline at end of file
@@ -1,20 +1,24 @@
+use std::{fmt::Display, str::FromStr};
+
 use aoc_runner_derive::aoc;
 use itertools::Itertools;
 use nom::{IResult, combinator::{map_res, all_consuming}, character::{complete::{space1, line_ending}, is_digit}, multi::separated_list1, bytes::complete::take_while1};
 
+use crate::diagnostics::DiagnosticsReport;
+
 
-fn parse_i32(input: &str) -> IResult<&str, i32> {
+fn parse_num<N: FromStr>(input: &str) -> IResult<&str, N> {
     map_res(
         take_while1(|c: char| is_digit(c.try_into().unwrap()) || c == '-'),
-        |num| i32::from_str_radix(num, 10)
+        |num: &str| num.parse::<N>()
     )(input)
 }
 
-fn parse_line(input: &str) -> IResult<&str, Vec<i32>> {
-    separated_list1(space1, parse_i32)(input)
+fn parse_line<N: FromStr>(input: &str) -> IResult<&str, Vec<N>> {
+    separated_list1(space1, parse_num)(input)
 }
 
-fn parse(input: &str) -> Vec<Vec<i32>> {
+fn parse<N: FromStr>(input: &str) -> Vec<Vec<N>> {
     let (_, output) = all_consuming(separated_list1(line_ending, parse_line))(input).unwrap();
 
     output
@@ -23,7 +27,7 @@ fn parse(input: &str) -> Vec<Vec<i32>> {
 
 #[aoc(day9, part1)]
 fn part1(input: &str) -> i32 {
-    let input = parse(input);
+    let input = parse::<i32>(input);
 
     input.into_iter().map(|line| {
         let mut line_derivs = vec![line];
@@ -47,7 +51,7 @@ fn part1(input: &str) -> i32 {
 
 #[aoc(day9, part2)]
 fn part2(input: &str) -> i32 {
-    let input = parse(input);
+    let input = parse::<i32>(input);
 
     input.into_iter().map(|line| {
         let mut line_derivs = vec![line];
@@ -68,6 +72,157 @@ fn part2(input: &str) -> i32 {
     }).sum()
 }
 
+// a sequence reduced to the first value of each row of its difference pyramid, which is all
+// Newton's forward difference formula needs to give the value at any position:
+//   y(x) = sum over k of choose(x, k) * coefficients[k]
+#[derive(Debug, PartialEq)]
+struct NewtonSeries {
+    coefficients: Vec<i128>,
+    len: usize
+}
+impl NewtonSeries {
+    // None if a difference overflows
+    fn from_sequence(values: &[i64]) -> Option<Self> {
+        let mut row = values.iter().map(|&value| value as i128).collect_vec();
+        let mut coefficients = vec![];
+        while !row.is_empty() && !row.iter().all(|&value| value == 0) {
+            coefficients.push(row[0]);
+            row = row.iter().tuple_windows().map(|(a, b)| b.checked_sub(*a)).collect::<Option<_>>()?;
+        }
+        Some(Self { coefficients, len: values.len() })
+    }
+
+    // a sequence only proves itself polynomial if some difference row of at least two values is constant;
+    // otherwise the last difference is how far the final value strays from the best fit of the rest
+    fn residual(&self) -> Option<i128> {
+        self.coefficients.last().copied().filter(|_| self.coefficients.len() == self.len)
+    }
+
+    // degree of the lowest polynomial that fits, a constant (or all zero) sequence is degree 0
+    fn degree(&self) -> usize {
+        self.coefficients.len().saturating_sub(1)
+    }
+
+    // value at position x of the sequence, 0 being the first given value; None if it overflows
+    fn value_at(&self, x: i64) -> Option<i128> {
+        let x = x as i128;
+        let mut binomial = 1i128;
+        let mut value = 0i128;
+        for (k, &coefficient) in self.coefficients.iter().enumerate() {
+            if k > 0 {
+                // choose(x, k) = choose(x, k - 1) * (x - k + 1) / k, which always divides exactly
+                binomial = binomial.checked_mul(x - k as i128 + 1)? / k as i128;
+            }
+            value = value.checked_add(binomial.checked_mul(coefficient)?)?;
+        }
+        Some(value)
+    }
+
+    // positive steps continue past the last value, negative steps go back before the first
+    fn extrapolate(&self, steps: i64) -> Option<i128> {
+        if steps >= 0 {
+            self.value_at(self.len as i64 - 1 + steps)
+        } else {
+            self.value_at(steps)
+        }
+    }
+}
+
+// lines count from 1, as in the input file
+#[derive(Debug, PartialEq)]
+enum OasisError {
+    NotPolynomial { line: usize, residual: i128 },
+    Overflow { line: usize }
+}
+impl Display for OasisError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotPolynomial { line, residual } => write!(f, "line {line}: not a polynomial, last value is {residual} off the best fit"),
+            Self::Overflow { line } => write!(f, "line {line}: differences overflow")
+        }
+    }
+}
+
+// what to do with a sequence whose differences never settle into a constant row
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum UnfitPolicy {
+    Error,
+    // extrapolate the polynomial running through every value anyway
+    BestFit,
+    Skip
+}
+
+fn validate(sequences: &[Vec<i64>]) -> Vec<OasisError> {
+    sequences.iter().zip(1..).filter_map(|(values, line)| match NewtonSeries::from_sequence(values) {
+        None => Some(OasisError::Overflow { line }),
+        Some(series) => series.residual().map(|residual| OasisError::NotPolynomial { line, residual })
+    }).collect()
+}
+
+// extrapolates every sequence, skipped sequences come back as None
+fn extrapolate_checked(sequences: &[Vec<i64>], steps: i64, policy: UnfitPolicy) -> Result<Vec<Option<i128>>, OasisError> {
+    sequences.iter().zip(1..).map(|(values, line)| {
+        let series = NewtonSeries::from_sequence(values).ok_or(OasisError::Overflow { line })?;
+        match (series.residual(), policy) {
+            (Some(residual), UnfitPolicy::Error) => Err(OasisError::NotPolynomial { line, residual }),
+            (Some(_), UnfitPolicy::Skip) => Ok(None),
+            _ => series.extrapolate(steps).map(Some).ok_or(OasisError::Overflow { line })
+        }
+    }).collect()
+}
+
+#[aoc(day9, part1, newton)]
+fn part1_newton(input: &str) -> i128 {
+    let input = parse::<i64>(input);
+
+    input.iter().map(|line| NewtonSeries::from_sequence(line).and_then(|series| series.extrapolate(1)).unwrap()).sum()
+}
+
+#[aoc(day9, part2, newton)]
+fn part2_newton(input: &str) -> i128 {
+    let input = parse::<i64>(input);
+
+    input.iter().map(|line| NewtonSeries::from_sequence(line).and_then(|series| series.extrapolate(-1)).unwrap()).sum()
+}
+
+// the polynomial degree found for each sequence along with the values either side of it
+#[aoc(day9, part1, degrees)]
+fn part1_degrees(input: &str) -> String {
+    let input = parse::<i64>(input);
+
+    let mut lines = input.iter().enumerate().map(|(idx, line)| match NewtonSeries::from_sequence(line) {
+        Some(series) => format!("line {}: degree {}, {} before, {} after", idx + 1, series.degree(),
+            series.extrapolate(-1).map_or("overflow".to_string(), |value| value.to_string()),
+            series.extrapolate(1).map_or("overflow".to_string(), |value| value.to_string())),
+        None => format!("line {}: differences overflow", idx + 1)
+    });
+    format!("\n{}", lines.join("\n"))
+}
+
+#[aoc(day9, part1, validate)]
+fn part1_validate(input: &str) -> DiagnosticsReport<OasisError> {
+    DiagnosticsReport(validate(&parse::<i64>(input)))
+}
+
+fn sum_checked(input: &str, steps: i64, policy: UnfitPolicy) -> String {
+    extrapolate_checked(&parse::<i64>(input), steps, policy)
+        .map_or_else(|error| error.to_string(), |values| values.into_iter().flatten().sum::<i128>().to_string())
+}
+
+#[aoc(day9, part1, strict)]
+fn part1_strict(input: &str) -> String {
+    sum_checked(input, 1, UnfitPolicy::Error)
+}
+
+#[aoc(day9, part1, best_fit)]
+fn part1_best_fit(input: &str) -> String {
+    sum_checked(input, 1, UnfitPolicy::BestFit)
+}
+
+#[aoc(day9, part1, skip_unfit)]
+fn part1_skip_unfit(input: &str) -> String {
+    sum_checked(input, 1, UnfitPolicy::Skip)
+}
 
 #[cfg(test)]
 mod tests {
@@ -87,4 +242,99 @@ mod tests {
 10 13 16 21 30 45"), 2);
     }
 
+    #[test]
+    fn part1_newton_example() {
+        assert_eq!(part1_newton("0 3 6 9 12 15
+1 3 6 10 15 21
+10 13 16 21 30 45"), 114);
+    }
+
+    #[test]
+    fn part2_newton_example() {
+        assert_eq!(part2_newton("0 3 6 9 12 15
+1 3 6 10 15 21
+10 13 16 21 30 45"), 2);
+    }
+
+    #[test]
+    fn part1_degrees_example() {
+        assert_eq!(part1_degrees("0 3 6 9 12 15
+1 3 6 10 15 21
+10 13 16 21 30 45"), "
+line 1: degree 1, -3 before, 18 after
+line 2: degree 2, 0 before, 28 after
+line 3: degree 3, 5 before, 68 after");
+    }
+
+    #[test]
+    fn newton_series() {
+        let series = NewtonSeries::from_sequence(&[10, 13, 16, 21, 30, 45]).unwrap();
+        assert_eq!(series, NewtonSeries { coefficients: vec![10, 3, 0, 2], len: 6 });
+        assert_eq!(series.degree(), 3);
+        assert_eq!((-2..=2).map(|steps| series.extrapolate(steps).unwrap()).collect_vec(), vec![-4, 5, 45, 68, 101]);
+
+        let linear = NewtonSeries::from_sequence(&[0, 3, 6, 9, 12, 15]).unwrap();
+        assert_eq!(linear.degree(), 1);
+        assert_eq!(linear.extrapolate(10), Some(45));
+        assert_eq!(linear.extrapolate(-3), Some(-9));
+
+        assert_eq!(NewtonSeries::from_sequence(&[7, 7, 7]).unwrap().degree(), 0);
+        assert_eq!(NewtonSeries::from_sequence(&[0, 0]).unwrap().extrapolate(5), Some(0));
+    }
+
+    #[test]
+    fn newton_past_i32() {
+        // fourth powers of 1000..1010 are far beyond i32, and so are their differences
+        let values = (1000i64..1010).map(|n| n.pow(4)).collect_vec();
+        let series = NewtonSeries::from_sequence(&values).unwrap();
+        assert_eq!(series.degree(), 4);
+        assert_eq!(series.extrapolate(1), Some(1010i128.pow(4)));
+        assert_eq!(series.extrapolate(1_000), Some(2009i128.pow(4)));
+        assert_eq!(series.extrapolate(-1_000), Some(0));
+
+        // differences of alternating extremes no longer fit
+        assert_eq!(NewtonSeries::from_sequence(&[i64::MAX, i64::MIN, i64::MAX]).and_then(|series| series.extrapolate(1 << 40)), None);
+    }
+
+    #[test]
+    fn non_polynomial_sequences() {
+        let sequences = parse::<i64>("0 3 6 9 12 15
+1 2 4 8 16 32
+5 5
+-4");
+        assert_eq!(validate(&sequences), vec![
+            OasisError::NotPolynomial { line: 2, residual: 1 },
+            OasisError::NotPolynomial { line: 4, residual: -4 }
+        ]);
+
+        assert_eq!(extrapolate_checked(&sequences, 1, UnfitPolicy::Error), Err(OasisError::NotPolynomial { line: 2, residual: 1 }));
+        assert_eq!(extrapolate_checked(&sequences, 1, UnfitPolicy::Skip), Ok(vec![Some(18), None, Some(5), None]));
+        // the degree five polynomial through 1, 2, 4, 8, 16, 32 continues with 63, not 64
+        assert_eq!(extrapolate_checked(&sequences, 1, UnfitPolicy::BestFit), Ok(vec![Some(18), Some(63), Some(5), Some(-4)]));
+
+        // each difference row of alternating extremes doubles, until it no longer fits in an i128
+        let alternating = (0..70).map(|ix| if ix % 2 == 0 { i64::MAX } else { i64::MIN }).collect_vec();
+        assert_eq!(validate(&[alternating]), vec![OasisError::Overflow { line: 1 }]);
+    }
+
+    #[test]
+    fn part1_policies() {
+        let input = "0 3 6 9 12 15
+1 2 4 8 16 32
+5 5
+-4";
+        assert_eq!(part1_validate(input).to_string(), "
+line 2: not a polynomial, last value is 1 off the best fit
+line 4: not a polynomial, last value is -4 off the best fit");
+        assert_eq!(part1_strict(input), "line 2: not a polynomial, last value is 1 off the best fit");
+        assert_eq!(part1_best_fit(input), (18 + 63 + 5 - 4).to_string());
+        assert_eq!(part1_skip_unfit(input), (18 + 5).to_string());
+
+        let example = "0 3 6 9 12 15
+1 3 6 10 15 21
+10 13 16 21 30 45";
+        assert_eq!(part1_validate(example).to_string(), "no problems found");
+        assert_eq!(part1_strict(example), "114");
+    }
+
 }
\ No newline at end of file
@@ -0,0 +1,18 @@
+use std::fmt::Display;
+
+
+// what a validator found, one problem per line, for runner variants that check an input rather than
+// solve it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiagnosticsReport<T>(pub Vec<T>);
+impl<T: Display> Display for DiagnosticsReport<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.0.is_empty() {
+            return write!(f, "no problems found");
+        }
+        for diagnostic in &self.0 {
+            write!(f, "\n{diagnostic}")?;
+        }
+        Ok(())
+    }
+}
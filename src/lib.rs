@@ -22,4 +22,9 @@ mod day19;
 mod day20;
 mod day21;
 
+mod diagnostics;
+
+#[cfg(test)]
+mod test_rng;
+
 aoc_lib!{year = 2023}
\ No newline at end of file
@@ -0,0 +1,28 @@
+// xorshift64, enough to build repeatable random inputs for tests without pulling in a rand crate
+pub struct TestRng(u64);
+impl TestRng {
+    // the seed must not be zero, xorshift never leaves the all-zero state
+    pub fn new(seed: u64) -> Self {
+        assert_ne!(seed, 0, "xorshift needs a non-zero seed");
+        Self(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    pub fn below(&mut self, bound: usize) -> usize {
+        (self.next_u64() % bound as u64) as usize
+    }
+
+    pub fn pick<T: Copy>(&mut self, choices: &[T]) -> T {
+        choices[self.below(choices.len())]
+    }
+
+    pub fn grid<T>(&mut self, rows: usize, cols: usize, mut cell: impl FnMut(&mut Self) -> T) -> Vec<Vec<T>> {
+        (0..rows).map(|_| (0..cols).map(|_| cell(self)).collect()).collect()
+    }
+}